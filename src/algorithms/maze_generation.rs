@@ -1,160 +1,1063 @@
-use rand::seq::SliceRandom;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-
-use termsize;
+use std::sync::Arc;
 
-use crate::helpers::engine_helpers::print_framerate;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 
-use crate::data::data_structures::Maze;
-use crate::menu::maze_scene::MazeScene;
+use crate::data::data_structures::{Direction, DisjointSet, Maze, Provenance};
+use crate::data::maze_mask::Mask;
+use crate::helpers::playback::Playback;
+use crate::menu::maze_runner::run_animation;
+use crate::menu::maze_scene::Highlights;
 
 // This file contains the maze generation algorithms.
 // Use multiple threads to run the algorithms  and visualize them in the console.
 
-pub fn recursive_backtracker() {
-    println!("Running Recursive Backtracker...");
-    thread::sleep(Duration::from_secs(2));
+pub const MAZE_WIDTH: usize = 25;
+pub const MAZE_HEIGHT: usize = 25;
+
+/// Everything needed to reproduce a maze and its animation with a given generator
+#[derive(Clone)]
+pub struct MazeSettings {
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub terrain: TerrainMode,
+    // Percentage of the dead ends removed after generation, adding loops
+    pub braid: u8,
+    // Percentage of the cells walled off by filling dead ends after generation
+    pub sparseness: u8,
+    // Shape of the maze, which then has the size of the mask instead of `width` x `height`
+    pub mask: Option<Mask>,
 }
 
-pub fn prim_algorithm() {
-    println!("Running Randomized Prim's Algorithm...");
-    thread::sleep(Duration::from_secs(2));
+/// How terrain is painted on a generated maze
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TerrainMode {
+    // Roads everywhere, every step costs the same
+    Uniform,
+    Random,
+    Noise,
 }
 
-pub fn kruskal_algorithm(engine: &mut console_engine::ConsoleEngine) {
-    let width = 25;
-    let height = 25;
-    let maze = Arc::new(Mutex::new(Maze::new(width, height)));
-    let screen_size = termsize::get().unwrap();
-    let scene = Arc::new(Mutex::new(MazeScene::new(
-        maze.lock().unwrap().clone(),
-        screen_size.cols as i32 / 2 - width as i32,
-        screen_size.rows as i32 / 2 - height as i32,
-        2,
-    )));
+impl TerrainMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TerrainMode::Uniform => "Uniform",
+            TerrainMode::Random => "Random",
+            TerrainMode::Noise => "Noise",
+        }
+    }
 
-    let maze_clone = Arc::clone(&maze);
-    let scene_clone = Arc::clone(&scene);
+    /// Next mode, to cycle through them in the menu
+    pub fn next(&self) -> TerrainMode {
+        match self {
+            TerrainMode::Uniform => TerrainMode::Random,
+            TerrainMode::Random => TerrainMode::Noise,
+            TerrainMode::Noise => TerrainMode::Uniform,
+        }
+    }
+}
+
+/// Result of a single generation step
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Continue,
+    Done,
+}
+
+/// A maze generation algorithm that can be advanced one step at a time.
+/// Implement this and register the generator in `run_maze_menu` to make it
+/// available, `run_generator` takes care of the thread and the rendering.
+pub trait MazeGenerator: Send {
+    /// Prepare the generator for `maze`. Every random choice must come from `rng`.
+    fn init(maze: &mut Maze, rng: StdRng) -> Self
+    where
+        Self: Sized;
 
-    let framerate = Arc::new(Mutex::new(1000));
-    let running = Arc::new(AtomicBool::new(true));
-    let running_clone = Arc::clone(&running);
+    /// Advance the algorithm by one visible step
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome;
+
+    fn is_done(&self) -> bool;
+
+    /// Generators that never finish, so they cannot be run to the end instantly
+    fn is_endless(&self) -> bool {
+        false
+    }
 
-    let thread = thread::spawn(move || {
-        while running_clone.load(Ordering::SeqCst) {
-            thread::sleep(Duration::from_millis(framerate.lock().unwrap().clone()));
-            kruskal_algorithm_thread(maze_clone.clone(), scene_clone.clone());
+    /// Algorithm state to draw on top of the maze
+    fn highlights(&self) -> Highlights;
+
+    fn outcome(&self) -> StepOutcome {
+        if self.is_done() {
+            StepOutcome::Done
+        } else {
+            StepOutcome::Continue
         }
+    }
+}
+
+/// Builds a boxed generator, used to register generators in the menu
+pub type GeneratorConstructor = fn(&mut Maze, StdRng) -> Box<dyn MazeGenerator>;
+
+pub fn create<G: MazeGenerator + 'static>(maze: &mut Maze, rng: StdRng) -> Box<dyn MazeGenerator> {
+    Box::new(G::init(maze, rng))
+}
+
+// Run a generation algorithm and animate it until 'q' is pressed.
+// Returns the maze if the generator finished, a maze left half carved is not
+// the maze its settings describe and is dropped.
+pub fn run_generator(
+    engine: &mut console_engine::ConsoleEngine,
+    name: &str,
+    create: GeneratorConstructor,
+    settings: MazeSettings,
+) -> Option<Maze> {
+    let mut maze = new_maze(&settings);
+    let mut generator = create(&mut maze, StdRng::seed_from_u64(settings.seed));
+    let description = format!(
+        "{}, {}x{}, seed {}",
+        name, maze.width, maze.height, settings.seed
+    );
+    let highlights = generator.highlights();
+    let playback = if generator.is_endless() {
+        Playback::endless()
+    } else {
+        Playback::new()
+    };
+
+    let finished = Arc::new(AtomicBool::new(false));
+    let finished_clone = Arc::clone(&finished);
+
+    let mut maze = run_animation(
+        engine,
+        maze,
+        &description,
+        highlights,
+        playback,
+        move |maze| {
+            let outcome = generator.step(maze);
+            if outcome == StepOutcome::Done {
+                finished_clone.store(true, Ordering::SeqCst);
+            }
+            (outcome, generator.highlights())
+        },
+    );
+    if !finished.load(Ordering::SeqCst) {
+        return None;
+    }
+    finish_maze(&mut maze, name, &settings);
+    Some(maze)
+}
+
+/// Run a generator to completion without animating it
+pub fn generate_maze(name: &str, create: GeneratorConstructor, settings: MazeSettings) -> Maze {
+    let mut maze = new_maze(&settings);
+    let mut generator = create(&mut maze, StdRng::seed_from_u64(settings.seed));
+    while generator.step(&mut maze) == StepOutcome::Continue {}
+    finish_maze(&mut maze, name, &settings);
+    maze
+}
+
+// Closed maze to run a generator on
+fn new_maze(settings: &MazeSettings) -> Maze {
+    match &settings.mask {
+        Some(mask) => Maze::with_mask(mask.clone()),
+        None => Maze::new(settings.width, settings.height),
+    }
+}
+
+// Post-processing, terrain and provenance, once the walls are done.
+// Terrain is painted last, some generators replace the maze
+fn finish_maze(maze: &mut Maze, name: &str, settings: &MazeSettings) {
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    // Generators working row by row or on rectangles can leave a masked maze
+    // in several parts
+    if maze.mask.is_some() {
+        maze.connect_regions(&mut rng);
+    }
+    // Sparsify first so that braiding does not reopen the filled cells
+    if settings.sparseness > 0 {
+        maze.sparsify(settings.sparseness, &mut rng);
+    }
+    if settings.braid > 0 {
+        maze.braid(settings.braid, &mut rng);
+    }
+    match settings.terrain {
+        TerrainMode::Uniform => {}
+        TerrainMode::Random => maze.paint_terrain_random(&mut rng),
+        TerrainMode::Noise => maze.paint_terrain_noise(&mut rng),
+    }
+    maze.origin = Some(Provenance {
+        generator: name.to_string(),
+        seed: settings.seed,
+        braid: settings.braid,
+        sparseness: settings.sparseness,
     });
+}
 
-    loop {
-        engine.wait_frame();
-        engine.clear_screen();
+/// Iterative depth-first backtracker.
+/// The head carves into a random unvisited neighbour, or pops back along the
+/// stack when every neighbour has been visited. Each step moves the head by one cell.
+pub struct RecursiveBacktracker {
+    pub stack: Vec<(i32, i32)>,
+    visited: i32,
+    rng: StdRng,
+}
 
-        {
-            let scene = scene.lock().unwrap();
-            scene.draw(engine, false, false, Vec::new(), false);
+impl MazeGenerator for RecursiveBacktracker {
+    fn init(maze: &mut Maze, mut rng: StdRng) -> RecursiveBacktracker {
+        let (x, y) = maze.get_random_cell(&mut rng);
+        maze.get_cell_mut(x, y).visit(0);
+        RecursiveBacktracker {
+            stack: vec![(x, y)],
+            visited: 1,
+            rng,
         }
+    }
 
-        if engine.is_key_pressed(console_engine::KeyCode::Char('q')) {
-            running.store(false, Ordering::SeqCst);
-            break;
+    fn is_done(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        let Some(&(x, y)) = self.stack.last() else {
+            return StepOutcome::Done;
+        };
+        let unvisited: Vec<(i32, i32)> = maze
+            .get_neighbors(x, y)
+            .into_iter()
+            .filter(|&(nx, ny)| !maze.get_cell(nx, ny).is_visited())
+            .collect();
+
+        match unvisited.choose(&mut self.rng) {
+            Some(&(nx, ny)) => {
+                maze.remove_wall(x, y, nx, ny);
+                maze.get_cell_mut(nx, ny).visit(self.visited);
+                self.visited += 1;
+                self.stack.push((nx, ny));
+            }
+            None => {
+                self.stack.pop();
+            }
+        }
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.stack.last().copied(),
+            stack: self.stack.clone(),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Randomized Prim's algorithm.
+/// The frontier holds every unvisited cell adjacent to the carved area. Each step
+/// picks a random frontier cell and connects it to a random carved neighbour.
+pub struct Prim {
+    pub frontier: Vec<(i32, i32)>,
+    /// Passage carved by the last step, for display
+    pub current: Option<(i32, i32, i32, i32)>,
+    visited: i32,
+    rng: StdRng,
+}
+
+impl Prim {
+    // Add a cell to the maze and its unvisited neighbours to the frontier
+    fn carve(&mut self, maze: &mut Maze, x: i32, y: i32) {
+        maze.get_cell_mut(x, y).visit(self.visited);
+        self.visited += 1;
+        for (nx, ny) in maze.get_neighbors(x, y) {
+            if !maze.get_cell(nx, ny).is_visited() && !self.frontier.contains(&(nx, ny)) {
+                self.frontier.push((nx, ny));
+            }
+        }
+    }
+}
+
+impl MazeGenerator for Prim {
+    fn init(maze: &mut Maze, rng: StdRng) -> Prim {
+        let mut prim = Prim {
+            frontier: Vec::new(),
+            current: None,
+            visited: 0,
+            rng,
+        };
+        let (x, y) = maze.get_random_cell(&mut prim.rng);
+        prim.carve(maze, x, y);
+        prim
+    }
+
+    fn is_done(&self) -> bool {
+        self.frontier.is_empty()
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.frontier.is_empty() {
+            self.current = None;
+            return StepOutcome::Done;
+        }
+        let index = self.rng.gen_range(0..self.frontier.len());
+        let (x, y) = self.frontier.swap_remove(index);
+
+        let carved: Vec<(i32, i32)> = maze
+            .get_neighbors(x, y)
+            .into_iter()
+            .filter(|&(nx, ny)| maze.get_cell(nx, ny).is_visited())
+            .collect();
+        if let Some(&(nx, ny)) = carved.choose(&mut self.rng) {
+            maze.remove_wall(x, y, nx, ny);
+            self.current = Some((x, y, nx, ny));
+        }
+        self.carve(maze, x, y);
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            current_wall: self.current,
+            frontier: self.frontier.clone(),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Resumable Kruskal's algorithm.
+/// Every wall between two adjacent cells is shuffled once up front, then each
+/// call to `step` examines walls until it removes exactly one (the first one
+/// separating two different sets). Generation is over when a single set remains.
+pub struct Kruskal {
+    width: usize,
+    sets: DisjointSet,
+    // Masked cells, each alone in its set
+    masked: usize,
+    walls: Vec<(i32, i32, i32, i32)>,
+    /// Wall removed by the last step, for display
+    pub current: Option<(i32, i32, i32, i32)>,
+    /// Walls the last step examined and kept, their cells being already joined
+    pub rejected: Vec<(i32, i32, i32, i32)>,
+}
+
+impl MazeGenerator for Kruskal {
+    fn init(maze: &mut Maze, mut rng: StdRng) -> Kruskal {
+        let mut walls = Vec::new();
+        for y in 0..maze.height as i32 {
+            for x in 0..maze.width as i32 {
+                for d in [Direction::East, Direction::South] {
+                    if let Some((nx, ny)) = maze.neighbor(x, y, d) {
+                        walls.push((x, y, nx, ny));
+                    }
+                }
+            }
+        }
+        walls.shuffle(&mut rng);
+
+        Kruskal {
+            width: maze.width,
+            sets: DisjointSet::new(maze.width * maze.height),
+            masked: maze.width * maze.height - maze.inside_count(),
+            walls,
+            current: None,
+            rejected: Vec::new(),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.sets.count() <= self.masked + 1 || self.walls.is_empty()
+    }
+
+    /// Remove the next wall joining two distinct sets
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        self.rejected.clear();
+        while let Some((x1, y1, x2, y2)) = self.walls.pop() {
+            let a = y1 as usize * self.width + x1 as usize;
+            let b = y2 as usize * self.width + x2 as usize;
+            if self.sets.union(a, b) {
+                maze.remove_wall(x1, y1, x2, y2);
+                self.current = Some((x1, y1, x2, y2));
+                break;
+            }
+            self.rejected.push((x1, y1, x2, y2));
+        }
+        if self.is_done() {
+            self.current = None;
+            self.rejected.clear();
+        }
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            current_wall: self.current,
+            rejected_walls: self.rejected.clone(),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Eller's algorithm.
+/// Each step generates one row: adjacent cells of different sets are randomly
+/// joined, then every set carves down at least once into the next row.
+/// The last row joins all remaining sets. In endless mode there is no last row
+/// and a new row is appended to the maze whenever the bottom is reached.
+pub struct Eller {
+    /// Next row to generate
+    pub row: i32,
+    // Set id of each cell of the current row, 0 when not assigned yet
+    sets: Vec<usize>,
+    next_set: usize,
+    endless: bool,
+    done: bool,
+    rng: StdRng,
+}
+
+impl Eller {
+    // Eller's algorithm only ever needs the current row, so it can keep adding rows
+    // at the bottom of the maze forever while the scene follows it.
+    pub fn endless(maze: &mut Maze, rng: StdRng) -> Eller {
+        Eller {
+            endless: true,
+            ..Eller::init(maze, rng)
+        }
+    }
+}
+
+impl MazeGenerator for Eller {
+    fn init(maze: &mut Maze, rng: StdRng) -> Eller {
+        Eller {
+            row: 0,
+            sets: vec![0; maze.width],
+            next_set: 1,
+            endless: false,
+            done: false,
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn is_endless(&self) -> bool {
+        self.endless
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.done {
+            return StepOutcome::Done;
+        }
+        let y = self.row;
+        let last_row = !self.endless && y as usize == maze.height - 1;
+
+        // Cells that were not carved into from above start a new set
+        for set in self.sets.iter_mut() {
+            if *set == 0 {
+                *set = self.next_set;
+                self.next_set += 1;
+            }
+        }
+        for x in 0..maze.width as i32 {
+            if maze.is_inside(x, y) {
+                maze.get_cell_mut(x, y).visit(self.sets[x as usize] as i32);
+            }
+        }
+
+        // Randomly join adjacent cells, the last row must join everything.
+        // Masked cells are never joined, so a set may end up cut off
+        for x in 0..maze.width - 1 {
+            let (a, b) = (self.sets[x], self.sets[x + 1]);
+            let open = maze.neighbor(x as i32, y, Direction::East).is_some();
+            if a != b && open && (last_row || self.rng.gen_bool(0.5)) {
+                maze.remove_wall(x as i32, y, x as i32 + 1, y);
+                for set in self.sets.iter_mut() {
+                    if *set == b {
+                        *set = a;
+                    }
+                }
+            }
+        }
+
+        if last_row {
+            self.done = true;
+            self.row += 1;
+            return StepOutcome::Done;
         }
 
-        print_framerate(engine);
-        print_thread_info(engine, &thread.thread());
+        // Each set carves down at least once
+        if y as usize + 1 >= maze.height {
+            maze.push_row();
+        }
+        let mut next_sets = vec![0; maze.width];
+        let mut columns: Vec<usize> = (0..maze.width).collect();
+        columns.shuffle(&mut self.rng);
+        let can_carve: Vec<bool> = (0..maze.width as i32)
+            .map(|x| maze.neighbor(x, y, Direction::South).is_some())
+            .collect();
+        for (i, &x) in columns.iter().enumerate() {
+            let set = self.sets[x];
+            let carved = next_sets.contains(&set);
+            let remaining = columns[i + 1..]
+                .iter()
+                .any(|&c| self.sets[c] == set && can_carve[c]);
+            if can_carve[x] && ((!carved && !remaining) || self.rng.gen_bool(0.3)) {
+                maze.remove_wall(x as i32, y, x as i32, y + 1);
+                next_sets[x] = set;
+            }
+        }
+        self.sets = next_sets;
+        self.row += 1;
+        StepOutcome::Continue
+    }
 
-        engine.draw();
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            row: if self.done { None } else { Some(self.row - 1) },
+            follow_bottom: self.endless,
+            ..Highlights::default()
+        }
     }
+}
 
-    // Wait for the thread to finish
-    thread.join().unwrap();
+/// Hunt-and-Kill algorithm.
+/// Walk phase: the head carves into random unvisited neighbours until it is stuck.
+/// Hunt phase: rows are scanned from the top, one per step, for an unvisited cell
+/// next to the carved area, which is joined to it and becomes the new head.
+pub struct HuntAndKill {
+    pub head: Option<(i32, i32)>,
+    /// Row being scanned during the hunt phase
+    pub scan_row: Option<i32>,
+    visited: i32,
+    done: bool,
+    rng: StdRng,
 }
 
-fn print_thread_info(engine: &mut console_engine::ConsoleEngine, thread: &thread::Thread) {
-    // print thread info
-    engine.print(0, 0, "Thread Info:");
-    engine.print(0, 3, "Algorithm: Kruskal's Algorithm");
-    engine.print(0, 4, "Speed: 1 step per second");
-    engine.print(0, 5, "Press 'q' to quit");
-    engine.print(0, 6, "Press 'p' to pause");
-    engine.print(0, 7, "Press 'r' to resume");
-    engine.print(0, 8, "Press 's' to step");
+impl HuntAndKill {
+    fn visit(&mut self, maze: &mut Maze, x: i32, y: i32) {
+        maze.get_cell_mut(x, y).visit(self.visited);
+        self.visited += 1;
+        self.head = Some((x, y));
+    }
 }
 
-fn kruskal_algorithm_thread(maze: Arc<Mutex<Maze>>, scene: Arc<Mutex<MazeScene>>) {
-    let mut maze = maze.lock().unwrap();
-    let mut scene = scene.lock().unwrap();
+impl MazeGenerator for HuntAndKill {
+    fn init(maze: &mut Maze, mut rng: StdRng) -> HuntAndKill {
+        let (x, y) = maze.get_random_cell(&mut rng);
+        maze.get_cell_mut(x, y).visit(0);
+        HuntAndKill {
+            head: Some((x, y)),
+            scan_row: None,
+            visited: 1,
+            done: false,
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if let Some((x, y)) = self.head {
+            let unvisited: Vec<(i32, i32)> = maze
+                .get_neighbors(x, y)
+                .into_iter()
+                .filter(|&(nx, ny)| !maze.get_cell(nx, ny).is_visited())
+                .collect();
+            match unvisited.choose(&mut self.rng) {
+                Some(&(nx, ny)) => {
+                    maze.remove_wall(x, y, nx, ny);
+                    self.visit(maze, nx, ny);
+                }
+                None => {
+                    self.head = None;
+                    self.scan_row = Some(0);
+                }
+            }
+            return StepOutcome::Continue;
+        }
 
-    kruskal_step(&mut maze, &mut scene)
+        let Some(y) = self.scan_row else {
+            return self.outcome();
+        };
+        for x in 0..maze.width as i32 {
+            if maze.get_cell(x, y).is_visited() {
+                continue;
+            }
+            let carved: Vec<(i32, i32)> = maze
+                .get_neighbors(x, y)
+                .into_iter()
+                .filter(|&(nx, ny)| maze.get_cell(nx, ny).is_visited())
+                .collect();
+            if let Some(&(nx, ny)) = carved.choose(&mut self.rng) {
+                maze.remove_wall(x, y, nx, ny);
+                self.visit(maze, x, y);
+                self.scan_row = None;
+                return StepOutcome::Continue;
+            }
+        }
+
+        if y as usize + 1 >= maze.height {
+            self.scan_row = None;
+            self.done = true;
+        } else {
+            self.scan_row = Some(y + 1);
+        }
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        let status = match self.scan_row {
+            Some(row) => format!("Phase: Hunt (scanning row {})", row),
+            None if self.done => "Phase: Done".to_string(),
+            None => "Phase: Walk".to_string(),
+        };
+        Highlights {
+            head: self.head,
+            row: self.scan_row,
+            status,
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Aldous-Broder algorithm.
+/// The cursor wanders to random neighbours, carving a passage whenever it enters
+/// an unvisited cell. Produces a uniform spanning tree, but slowly.
+pub struct AldousBroder {
+    pub cursor: (i32, i32),
+    /// Cells not visited yet
+    pub remaining: usize,
+    // Cells inside the mask
+    total: usize,
+    rng: StdRng,
 }
 
-fn kruskal_step(maze: &mut Maze, scene: &mut MazeScene) {
-    let mut sets: Vec<Vec<(usize, usize)>> = Vec::new();
-    for y in 0..maze.height {
-        for x in 0..maze.width {
-            let mut set = Vec::new();
-            set.push((x, y));
-            sets.push(set);
+impl MazeGenerator for AldousBroder {
+    fn init(maze: &mut Maze, mut rng: StdRng) -> AldousBroder {
+        let (x, y) = maze.get_random_cell(&mut rng);
+        maze.get_cell_mut(x, y).visit(0);
+        AldousBroder {
+            cursor: (x, y),
+            remaining: maze.inside_count() - 1,
+            total: maze.inside_count(),
+            rng,
         }
     }
-    let mut walls: Vec<(usize, usize, usize, usize)> = Vec::new();
-    for y in 0..maze.height {
-        for x in 0..maze.width {
-            if x < maze.width - 1 {
-                walls.push((x, y, x + 1, y));
+
+    fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.is_done() {
+            return StepOutcome::Done;
+        }
+        let (x, y) = self.cursor;
+        let (nx, ny) = maze.get_neighbor(x, y, &mut self.rng);
+        if !maze.get_cell(nx, ny).is_visited() {
+            maze.remove_wall(x, y, nx, ny);
+            let order = (self.total - self.remaining) as i32;
+            maze.get_cell_mut(nx, ny).visit(order);
+            self.remaining -= 1;
+        }
+        self.cursor = (nx, ny);
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: Some(self.cursor),
+            status: format!("Unvisited cells: {}", self.remaining),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Wilson's algorithm.
+/// A random walk starts from a cell outside the tree and wanders until it hits
+/// the tree. Whenever the walk crosses itself the loop is erased, and once it
+/// reaches the tree the loop-erased path is carved. Produces a uniform spanning tree.
+pub struct Wilson {
+    /// Loop-erased walk in progress, from its start to the current cell
+    pub path: Vec<(i32, i32)>,
+    /// Cells not in the tree yet
+    pub remaining: usize,
+    // Cells inside the mask
+    total: usize,
+    rng: StdRng,
+}
+
+impl MazeGenerator for Wilson {
+    fn init(maze: &mut Maze, mut rng: StdRng) -> Wilson {
+        let (x, y) = maze.get_random_cell(&mut rng);
+        maze.get_cell_mut(x, y).visit(0);
+        Wilson {
+            path: Vec::new(),
+            remaining: maze.inside_count() - 1,
+            total: maze.inside_count(),
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.is_done() {
+            return StepOutcome::Done;
+        }
+
+        // Start a new walk from a random cell outside the tree
+        let Some(&(x, y)) = self.path.last() else {
+            let outside: Vec<(i32, i32)> = maze
+                .inside_cells()
+                .into_iter()
+                .filter(|&(x, y)| !maze.get_cell(x, y).is_visited())
+                .collect();
+            if let Some(&start) = outside.choose(&mut self.rng) {
+                self.path.push(start);
             }
-            if y < maze.height - 1 {
-                walls.push((x, y, x, y + 1));
+            return StepOutcome::Continue;
+        };
+
+        let (nx, ny) = maze.get_neighbor(x, y, &mut self.rng);
+        if maze.get_cell(nx, ny).is_visited() {
+            // The walk reached the tree, carve it
+            self.path.push((nx, ny));
+            for i in 0..self.path.len() - 1 {
+                let (ax, ay) = self.path[i];
+                let (bx, by) = self.path[i + 1];
+                maze.remove_wall(ax, ay, bx, by);
+                let order = (self.total - self.remaining) as i32;
+                maze.get_cell_mut(ax, ay).visit(order);
+                self.remaining -= 1;
             }
+            self.path.clear();
+        } else if let Some(i) = self.path.iter().position(|&c| c == (nx, ny)) {
+            // Erase the loop
+            self.path.truncate(i + 1);
+        } else {
+            self.path.push((nx, ny));
+        }
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.path.last().copied(),
+            stack: self.path.clone(),
+            status: format!(
+                "Cells outside the tree: {}, walk length: {}",
+                self.remaining,
+                self.path.len()
+            ),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Binary tree algorithm.
+/// Cells are visited in reading order and each one carves either north or east.
+pub struct BinaryTree {
+    pub head: Option<(i32, i32)>,
+    next: usize,
+    done: bool,
+    rng: StdRng,
+}
+
+impl MazeGenerator for BinaryTree {
+    fn init(_maze: &mut Maze, rng: StdRng) -> BinaryTree {
+        BinaryTree {
+            head: None,
+            next: 0,
+            done: false,
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.next >= maze.width * maze.height {
+            self.head = None;
+            self.done = true;
+            return StepOutcome::Done;
         }
+        let x = (self.next % maze.width) as i32;
+        let y = (self.next / maze.width) as i32;
+        self.next += 1;
+        if !maze.is_inside(x, y) {
+            return StepOutcome::Continue;
+        }
+
+        let directions: Vec<(i32, i32)> = [Direction::North, Direction::East]
+            .into_iter()
+            .filter_map(|d| maze.neighbor(x, y, d))
+            .collect();
+        if let Some(&(nx, ny)) = directions.choose(&mut self.rng) {
+            maze.remove_wall(x, y, nx, ny);
+        }
+        maze.get_cell_mut(x, y).visit(self.next as i32);
+        self.head = Some((x, y));
+        StepOutcome::Continue
     }
-    walls.shuffle(&mut rand::thread_rng());
-    for wall in walls {
-        let (x1, y1, x2, y2) = wall;
-        let set1 = find_set(&sets, x1, y1);
-        let set2 = find_set(&sets, x2, y2);
-        if set1 != set2 {
-            maze.remove_wall(x1 as i32, y1 as i32, x2 as i32, y2 as i32);
-            let mut new_set = set1.clone();
-            new_set.append(&mut set2.clone());
-            sets.retain(|s| s != &set1 && s != &set2);
-            sets.push(new_set);
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.head,
+            ..Highlights::default()
         }
     }
-    scene.maze = maze.clone();
 }
 
-fn find_set(sets: &Vec<Vec<(usize, usize)>>, x: usize, y: usize) -> Vec<(usize, usize)> {
-    for set in sets {
-        for cell in set {
-            if cell == &(x, y) {
-                return set.clone();
+/// Sidewinder algorithm.
+/// Cells are visited in reading order and added to the current run. The run
+/// either continues east or is closed by carving north from one of its cells.
+/// The top row is a single corridor.
+pub struct Sidewinder {
+    /// Cells of the current run
+    pub run: Vec<(i32, i32)>,
+    next: usize,
+    done: bool,
+    rng: StdRng,
+}
+
+impl MazeGenerator for Sidewinder {
+    fn init(_maze: &mut Maze, rng: StdRng) -> Sidewinder {
+        Sidewinder {
+            run: Vec::new(),
+            next: 0,
+            done: false,
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.next >= maze.width * maze.height {
+            self.run.clear();
+            self.done = true;
+            return StepOutcome::Done;
+        }
+        let x = (self.next % maze.width) as i32;
+        let y = (self.next / maze.width) as i32;
+        self.next += 1;
+        if !maze.is_inside(x, y) {
+            return StepOutcome::Continue;
+        }
+
+        self.run.push((x, y));
+        maze.get_cell_mut(x, y).visit(self.next as i32);
+
+        // The east edge of the maze or of its mask ends the run
+        let at_east_edge = maze.neighbor(x, y, Direction::East).is_none();
+        let close_run = at_east_edge || (y > 0 && self.rng.gen_bool(0.5));
+        if close_run {
+            let northward: Vec<(i32, i32)> = self
+                .run
+                .iter()
+                .copied()
+                .filter(|&(rx, ry)| maze.neighbor(rx, ry, Direction::North).is_some())
+                .collect();
+            if let Some(&(rx, ry)) = northward.choose(&mut self.rng) {
+                maze.remove_wall(rx, ry, rx, ry - 1);
             }
+            self.run.clear();
+        } else {
+            maze.remove_wall(x, y, x + 1, y);
+        }
+        StepOutcome::Continue
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.run.last().copied(),
+            stack: self.run.clone(),
+            ..Highlights::default()
         }
     }
-    Vec::new()
 }
 
-pub fn eller_algorithm() {
-    println!("Running Randomized Eller's Algorithm...");
-    thread::sleep(Duration::from_secs(2));
+/// How the growing tree algorithm picks the next active cell
+#[derive(Clone, Copy)]
+pub enum GrowingTreePolicy {
+    /// Most recently added cell, behaves like the recursive backtracker
+    Newest,
+    /// Random cell, behaves like Prim's algorithm
+    Random,
+    /// First added cell, gives long straight corridors
+    Oldest,
+    /// Newest half of the time, random otherwise
+    Mixed,
 }
 
-pub fn hunt_and_kill() {
-    println!("Running Hunt-and-Kill Algorithm...");
-    thread::sleep(Duration::from_secs(2));
+/// Growing tree algorithm.
+/// Keeps a list of active cells. Each step picks one according to the policy and
+/// carves into a random unvisited neighbour, or retires it when there is none.
+pub struct GrowingTree {
+    pub active: Vec<(i32, i32)>,
+    pub head: Option<(i32, i32)>,
+    policy: GrowingTreePolicy,
+    visited: i32,
+    rng: StdRng,
 }
 
-pub fn aldous_broder() {
-    println!("Running Aldous-Broder Algorithm...");
-    thread::sleep(Duration::from_secs(2));
+impl GrowingTree {
+    pub fn with_policy(maze: &mut Maze, rng: StdRng, policy: GrowingTreePolicy) -> GrowingTree {
+        GrowingTree {
+            policy,
+            ..GrowingTree::init(maze, rng)
+        }
+    }
 }
 
-pub fn wilson_algorithm() {
-    println!("Running Wilson's Algorithm...");
-    thread::sleep(Duration::from_secs(2));
+impl MazeGenerator for GrowingTree {
+    fn init(maze: &mut Maze, mut rng: StdRng) -> GrowingTree {
+        let (x, y) = maze.get_random_cell(&mut rng);
+        maze.get_cell_mut(x, y).visit(0);
+        GrowingTree {
+            active: vec![(x, y)],
+            head: Some((x, y)),
+            policy: GrowingTreePolicy::Newest,
+            visited: 1,
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.active.is_empty() {
+            self.head = None;
+            return StepOutcome::Done;
+        }
+        let newest = self.active.len() - 1;
+        let index = match self.policy {
+            GrowingTreePolicy::Newest => newest,
+            GrowingTreePolicy::Random => self.rng.gen_range(0..self.active.len()),
+            GrowingTreePolicy::Oldest => 0,
+            GrowingTreePolicy::Mixed => {
+                if self.rng.gen_bool(0.5) {
+                    newest
+                } else {
+                    self.rng.gen_range(0..self.active.len())
+                }
+            }
+        };
+        let (x, y) = self.active[index];
+        self.head = Some((x, y));
+
+        let unvisited: Vec<(i32, i32)> = maze
+            .get_neighbors(x, y)
+            .into_iter()
+            .filter(|&(nx, ny)| !maze.get_cell(nx, ny).is_visited())
+            .collect();
+        match unvisited.choose(&mut self.rng) {
+            Some(&(nx, ny)) => {
+                maze.remove_wall(x, y, nx, ny);
+                maze.get_cell_mut(nx, ny).visit(self.visited);
+                self.visited += 1;
+                self.active.push((nx, ny));
+            }
+            None => {
+                self.active.remove(index);
+            }
+        }
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.head,
+            frontier: self.active.clone(),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Recursive division algorithm.
+/// Starts from an open grid and adds walls instead of carving passages. Each step
+/// splits a chamber in two with a wall line that has a single gap in it.
+pub struct RecursiveDivision {
+    /// Chambers still to divide, as (x, y, width, height) in cells
+    pub chambers: Vec<(i32, i32, i32, i32)>,
+    /// Gap left in the last wall line, for display
+    pub gap: Option<(i32, i32, i32, i32)>,
+    rng: StdRng,
+}
+
+impl MazeGenerator for RecursiveDivision {
+    fn init(maze: &mut Maze, rng: StdRng) -> RecursiveDivision {
+        maze.open_all();
+        RecursiveDivision {
+            chambers: vec![(0, 0, maze.width as i32, maze.height as i32)],
+            gap: None,
+            rng,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.chambers.is_empty()
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        self.gap = None;
+
+        while let Some((x, y, w, h)) = self.chambers.pop() {
+            if w < 2 || h < 2 {
+                continue;
+            }
+            let horizontal = if w == h {
+                self.rng.gen_bool(0.5)
+            } else {
+                h > w
+            };
+            if horizontal {
+                // Wall between rows wy and wy + 1
+                let wy = self.rng.gen_range(y..y + h - 1);
+                let gx = self.rng.gen_range(x..x + w);
+                for cx in x..x + w {
+                    if cx != gx {
+                        maze.add_wall(cx, wy, cx, wy + 1);
+                    }
+                }
+                self.gap = Some((gx, wy, gx, wy + 1));
+                self.chambers.push((x, y, w, wy - y + 1));
+                self.chambers.push((x, wy + 1, w, y + h - wy - 1));
+            } else {
+                // Wall between columns wx and wx + 1
+                let wx = self.rng.gen_range(x..x + w - 1);
+                let gy = self.rng.gen_range(y..y + h);
+                for cy in y..y + h {
+                    if cy != gy {
+                        maze.add_wall(wx, cy, wx + 1, cy);
+                    }
+                }
+                self.gap = Some((wx, gy, wx + 1, gy));
+                self.chambers.push((x, y, wx - x + 1, h));
+                self.chambers.push((wx + 1, y, x + w - wx - 1, h));
+            }
+            break;
+        }
+        self.outcome()
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            current_wall: self.gap,
+            status: format!("Chambers left: {}", self.chambers.len()),
+            ..Highlights::default()
+        }
+    }
 }
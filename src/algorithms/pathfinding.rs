@@ -1,13 +1,1188 @@
-use std::thread;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 
-pub fn bfs() {
-    println!("Running Breadth First Search...");
-    thread::sleep(Duration::from_secs(2));
+use crate::algorithms::maze_generation::StepOutcome;
+use crate::data::data_structures::{Direction, Maze, SolverRun};
+use crate::helpers::playback::Playback;
+use crate::menu::maze_runner::{run_animation, run_race, Racer};
+use crate::menu::maze_scene::Highlights;
+
+// This file contains the pathfinding algorithms.
+// They search from `maze.start` to `maze.goal` one cell per step, marking the
+// explored cells with `Cell::visit`, and are animated through the maze runner.
+// BFS and DFS ignore the terrain, Dijkstra and A* minimize the terrain cost.
+
+/// A pathfinding algorithm that can be advanced one step at a time
+pub trait Solver: Send {
+    /// Prepare the search on `maze`, whose visited flags have been cleared
+    fn init(maze: &mut Maze) -> Self
+    where
+        Self: Sized;
+
+    /// Expand one cell
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome;
+
+    fn is_done(&self) -> bool;
+
+    /// Search state to draw on top of the maze
+    fn highlights(&self) -> Highlights;
+
+    fn outcome(&self) -> StepOutcome {
+        if self.is_done() {
+            StepOutcome::Done
+        } else {
+            StepOutcome::Continue
+        }
+    }
+}
+
+/// Builds a boxed solver, used to register solvers in the menu
+pub type SolverConstructor = fn(&mut Maze) -> Box<dyn Solver>;
+
+pub fn create<S: Solver + 'static>(maze: &mut Maze) -> Box<dyn Solver> {
+    Box::new(S::init(maze))
+}
+
+// Run a solver on a copy of `maze` and animate it until 'q' is pressed.
+// Returns the result if the solver finished.
+pub fn run_solver(
+    engine: &mut console_engine::ConsoleEngine,
+    name: &str,
+    create: SolverConstructor,
+    maze: &Maze,
+) -> Option<SolverRun> {
+    let mut maze = maze.clone();
+    maze.reset_visited();
+    let mut solver = create(&mut maze);
+    let highlights = solver.highlights();
+
+    let result: Arc<Mutex<Option<SolverRun>>> = Arc::new(Mutex::new(None));
+    let result_clone = Arc::clone(&result);
+    let solver_name = name.to_string();
+    let mut visit_order: Vec<(i32, i32)> = Vec::new();
+
+    run_animation(
+        engine,
+        maze,
+        name,
+        highlights,
+        Playback::new(),
+        move |maze| {
+            let outcome = solver.step(maze);
+            let highlights = solver.highlights();
+            if let Some(head) = highlights.head {
+                if visit_order.last() != Some(&head) {
+                    visit_order.push(head);
+                }
+            }
+            if outcome == StepOutcome::Done {
+                *result_clone.lock().unwrap() = Some(SolverRun {
+                    solver: solver_name.clone(),
+                    path_cost: path_cost(maze, &highlights.path),
+                    path: highlights.path.clone(),
+                    visit_order: visit_order.clone(),
+                });
+            }
+            (outcome, highlights)
+        },
+    );
+
+    let run = result.lock().unwrap().take();
+    run
+}
+
+// Run several solvers on copies of `maze` side by side, in lockstep
+pub fn run_solver_race(
+    engine: &mut console_engine::ConsoleEngine,
+    solvers: &[(&str, SolverConstructor)],
+    maze: &Maze,
+) {
+    let mut maze = maze.clone();
+    maze.reset_visited();
+    let racers = solvers
+        .iter()
+        .map(|&(name, create)| {
+            let mut race_maze = maze.clone();
+            let mut solver = create(&mut race_maze);
+            Racer {
+                name: name.to_string(),
+                maze: race_maze,
+                highlights: solver.highlights(),
+                step: Box::new(move |maze| {
+                    let outcome = solver.step(maze);
+                    (outcome, solver.highlights())
+                }),
+            }
+        })
+        .collect();
+    let description = format!("Race, {}x{}", maze.width, maze.height);
+    run_race(engine, &description, racers);
+}
+
+// Cell each cell was reached from, indexed by [y][x]
+type Parents = Vec<Vec<Option<(i32, i32)>>>;
+
+// Walk the parent links back from the goal
+fn reconstruct_path(parents: &Parents, goal: (i32, i32)) -> Vec<(i32, i32)> {
+    let mut path = vec![goal];
+    let mut current = goal;
+    while let Some(parent) = parents[current.1 as usize][current.0 as usize] {
+        path.push(parent);
+        current = parent;
+    }
+    path.reverse();
+    path
+}
+
+// Sum of the terrain costs along `path`, the start cell is free
+fn path_cost(maze: &Maze, path: &[(i32, i32)]) -> i32 {
+    path.iter().skip(1).map(|&(x, y)| maze.cost(x, y)).sum()
+}
+
+fn search_status(expanded: usize, done: bool, path: Option<&Vec<(i32, i32)>>, cost: i32) -> String {
+    match (done, path) {
+        (true, Some(path)) => format!(
+            "Expanded: {}, path length: {}, cost: {}",
+            expanded,
+            path.len(),
+            cost
+        ),
+        (true, None) => format!("Expanded: {}, no path found", expanded),
+        _ => format!("Expanded: {}", expanded),
+    }
+}
+
+/// Breadth first search.
+/// Expands cells in order of distance from the start, so the path found is the shortest.
+pub struct Bfs {
+    queue: VecDeque<(i32, i32)>,
+    parents: Parents,
+    goal: (i32, i32),
+    head: Option<(i32, i32)>,
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
+}
+
+impl Solver for Bfs {
+    fn init(maze: &mut Maze) -> Bfs {
+        let (x, y) = maze.start;
+        maze.get_cell_mut(x, y).visit(0);
+        Bfs {
+            queue: VecDeque::from([maze.start]),
+            parents: vec![vec![None; maze.width]; maze.height],
+            goal: maze.goal,
+            head: None,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: false,
+        }
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        let Some((x, y)) = self.queue.pop_front() else {
+            self.done = true;
+            return StepOutcome::Done;
+        };
+        self.head = Some((x, y));
+        self.expanded += 1;
+
+        if (x, y) == self.goal {
+            let path = reconstruct_path(&self.parents, self.goal);
+            self.path_cost = path_cost(maze, &path);
+            self.path = Some(path);
+            self.done = true;
+            return StepOutcome::Done;
+        }
+
+        let distance = maze.get_cell(x, y).value;
+        for (nx, ny) in maze.open_neighbors(x, y) {
+            if !maze.get_cell(nx, ny).is_visited() {
+                maze.get_cell_mut(nx, ny).visit(distance + 1);
+                self.parents[ny as usize][nx as usize] = Some((x, y));
+                self.queue.push_back((nx, ny));
+            }
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.head,
+            frontier: self.queue.iter().copied().collect(),
+            path: self.path.clone().unwrap_or_default(),
+            status: search_status(self.expanded, self.done, self.path.as_ref(), self.path_cost),
+            ..Highlights::default()
+        }
+    }
+}
+
+// Distance from the search origin, indexed by [y][x]
+type Distances = Vec<Vec<Option<i32>>>;
+
+// Two neighbouring cells reached by the two searches, and the length of the path through them
+#[derive(Clone, Copy)]
+struct Meeting {
+    length: i32,
+    // Reached from the start
+    forward: (i32, i32),
+    // Reached from the goal
+    backward: (i32, i32),
+}
+
+/// Bidirectional breadth first search.
+/// One BFS grows from the start and another from the goal, taking turns. When
+/// they touch, the best meeting found is kept until no shorter one can exist,
+/// so the path found is as short as with a single BFS.
+pub struct BidirectionalBfs {
+    // Index 0 searches from the start, index 1 from the goal
+    queues: [VecDeque<(i32, i32)>; 2],
+    distances: [Distances; 2],
+    parents: [Parents; 2],
+    side: usize,
+    // Shortest meeting found so far
+    best: Option<Meeting>,
+    head: Option<(i32, i32)>,
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
+}
+
+impl BidirectionalBfs {
+    // Distance of the next cell the given side will expand
+    fn front_distance(&self, side: usize) -> Option<i32> {
+        let (x, y) = *self.queues[side].front()?;
+        self.distances[side][y as usize][x as usize]
+    }
+
+    // No meeting shorter than the best one is possible once the two fronts are far enough
+    fn finished(&self) -> bool {
+        match (self.front_distance(0), self.front_distance(1), self.best) {
+            (Some(forward), Some(backward), Some(best)) => forward + backward + 1 >= best.length,
+            (Some(_), Some(_), None) => false,
+            // One side ran out of cells, every meeting has been found
+            _ => true,
+        }
+    }
+}
+
+impl Solver for BidirectionalBfs {
+    fn init(maze: &mut Maze) -> BidirectionalBfs {
+        let mut distances = [
+            vec![vec![None; maze.width]; maze.height],
+            vec![vec![None; maze.width]; maze.height],
+        ];
+        for (side, (x, y)) in [maze.start, maze.goal].into_iter().enumerate() {
+            distances[side][y as usize][x as usize] = Some(0);
+            maze.get_cell_mut(x, y).visit(0);
+        }
+        let best = if maze.start == maze.goal {
+            Some(Meeting {
+                length: 0,
+                forward: maze.start,
+                backward: maze.goal,
+            })
+        } else {
+            None
+        };
+        BidirectionalBfs {
+            queues: [VecDeque::from([maze.start]), VecDeque::from([maze.goal])],
+            distances,
+            parents: [
+                vec![vec![None; maze.width]; maze.height],
+                vec![vec![None; maze.width]; maze.height],
+            ],
+            side: 0,
+            best,
+            head: None,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: false,
+        }
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.finished() {
+            if let Some(Meeting {
+                forward, backward, ..
+            }) = self.best
+            {
+                // The goal side parents lead back to the goal
+                let mut path = reconstruct_path(&self.parents[0], forward);
+                let mut rest = reconstruct_path(&self.parents[1], backward);
+                rest.reverse();
+                if forward == backward {
+                    rest.remove(0);
+                }
+                path.extend(rest);
+                self.path_cost = path_cost(maze, &path);
+                self.path = Some(path);
+            }
+            self.done = true;
+            return StepOutcome::Done;
+        }
+
+        let side = self.side;
+        let other = 1 - side;
+        self.side = other;
+        let Some((x, y)) = self.queues[side].pop_front() else {
+            return self.outcome();
+        };
+        self.head = Some((x, y));
+        self.expanded += 1;
+
+        let distance = self.distances[side][y as usize][x as usize].unwrap_or(0);
+        for (nx, ny) in maze.open_neighbors(x, y) {
+            if let Some(remaining) = self.distances[other][ny as usize][nx as usize] {
+                let length = distance + 1 + remaining;
+                if self.best.is_none_or(|best| length < best.length) {
+                    let (forward, backward) = if side == 0 {
+                        ((x, y), (nx, ny))
+                    } else {
+                        ((nx, ny), (x, y))
+                    };
+                    self.best = Some(Meeting {
+                        length,
+                        forward,
+                        backward,
+                    });
+                }
+            }
+            if self.distances[side][ny as usize][nx as usize].is_none() {
+                self.distances[side][ny as usize][nx as usize] = Some(distance + 1);
+                self.parents[side][ny as usize][nx as usize] = Some((x, y));
+                self.queues[side].push_back((nx, ny));
+                maze.get_cell_mut(nx, ny).visit(distance + 1);
+            }
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.head,
+            frontier: self.queues[0].iter().copied().collect(),
+            frontier_alt: self.queues[1].iter().copied().collect(),
+            path: self.path.clone().unwrap_or_default(),
+            status: search_status(self.expanded, self.done, self.path.as_ref(), self.path_cost),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Depth first search.
+/// Follows one corridor as deep as possible before backtracking, the path
+/// found is usually not the shortest.
+pub struct Dfs {
+    // Cells to explore, the most recent push of a cell also sets its parent
+    stack: Vec<(i32, i32)>,
+    parents: Parents,
+    goal: (i32, i32),
+    head: Option<(i32, i32)>,
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
+}
+
+impl Solver for Dfs {
+    fn init(maze: &mut Maze) -> Dfs {
+        Dfs {
+            stack: vec![maze.start],
+            parents: vec![vec![None; maze.width]; maze.height],
+            goal: maze.goal,
+            head: None,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: false,
+        }
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        // Skip cells that were reached another way since they were pushed
+        let (x, y) = loop {
+            match self.stack.pop() {
+                Some((x, y)) if maze.get_cell(x, y).is_visited() => continue,
+                Some(cell) => break cell,
+                None => {
+                    self.done = true;
+                    return StepOutcome::Done;
+                }
+            }
+        };
+        maze.get_cell_mut(x, y).visit(self.expanded as i32);
+        self.head = Some((x, y));
+        self.expanded += 1;
+
+        if (x, y) == self.goal {
+            let path = reconstruct_path(&self.parents, self.goal);
+            self.path_cost = path_cost(maze, &path);
+            self.path = Some(path);
+            self.done = true;
+            return StepOutcome::Done;
+        }
+
+        for (nx, ny) in maze.open_neighbors(x, y) {
+            if !maze.get_cell(nx, ny).is_visited() {
+                self.parents[ny as usize][nx as usize] = Some((x, y));
+                self.stack.push((nx, ny));
+            }
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: self.head,
+            frontier: self.stack.clone(),
+            path: self.path.clone().unwrap_or_default(),
+            status: search_status(self.expanded, self.done, self.path.as_ref(), self.path_cost),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Distance estimate from a cell to the goal used by A*.
+/// None of them overestimates, as the cheapest terrain costs 1 per cell.
+#[derive(Clone, Copy)]
+pub enum Heuristic {
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+    // Always 0, the search then expands cells like Dijkstra
+    Zero,
+}
+
+impl Heuristic {
+    pub fn estimate(&self, (x, y): (i32, i32), (gx, gy): (i32, i32)) -> f64 {
+        let dx = (x - gx).abs() as f64;
+        let dy = (y - gy).abs() as f64;
+        match self {
+            Heuristic::Manhattan => dx + dy,
+            Heuristic::Euclidean => (dx * dx + dy * dy).sqrt(),
+            Heuristic::Chebyshev => dx.max(dy),
+            Heuristic::Zero => 0.0,
+        }
+    }
+}
+
+// Entry of the A* open set, a cell may be pushed again when a cheaper way is found
+struct OpenEntry {
+    cell: (i32, i32),
+    g: i32,
+    f: f64,
 }
 
-pub fn dfs() {
-    println!("Running Depth First Search...");
-    thread::sleep(Duration::from_secs(2));
+/// A* search.
+/// Expands the open cell with the lowest f = g + h, where g is the terrain cost
+/// from the start and h the heuristic estimate to the goal. Ties go to the deepest
+/// cell, which heads straight for the goal when several cells look as good.
+/// Closed cells are marked visited with their g cost as value.
+pub struct AStar {
+    heuristic: Heuristic,
+    // Break ties on the deepest cell, otherwise on the oldest entry
+    prefer_deeper: bool,
+    open: Vec<OpenEntry>,
+    // Best known cost from the start, indexed by [y][x]
+    g_costs: Vec<Vec<Option<i32>>>,
+    parents: Parents,
+    goal: (i32, i32),
+    head: Option<(i32, i32)>,
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
 }
 
+impl AStar {
+    pub fn with_heuristic(maze: &mut Maze, heuristic: Heuristic) -> AStar {
+        let mut g_costs = vec![vec![None; maze.width]; maze.height];
+        let (x, y) = maze.start;
+        g_costs[y as usize][x as usize] = Some(0);
+        AStar {
+            heuristic,
+            prefer_deeper: true,
+            open: vec![OpenEntry {
+                cell: maze.start,
+                g: 0,
+                f: heuristic.estimate(maze.start, maze.goal),
+            }],
+            g_costs,
+            parents: vec![vec![None; maze.width]; maze.height],
+            goal: maze.goal,
+            head: None,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: false,
+        }
+    }
+
+    /// Dijkstra's algorithm: no heuristic, and ties go to the cell that was
+    /// reached first, so the search grows evenly in every direction.
+    pub fn dijkstra(maze: &mut Maze) -> AStar {
+        AStar {
+            prefer_deeper: false,
+            ..AStar::with_heuristic(maze, Heuristic::Zero)
+        }
+    }
+
+    // Index of the open entry to expand next
+    fn best_entry(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for (i, entry) in self.open.iter().enumerate() {
+            let better = match best {
+                None => true,
+                Some(b) => {
+                    let current = &self.open[b];
+                    entry.f < current.f
+                        || (entry.f == current.f && self.prefer_deeper && entry.g > current.g)
+                }
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+        best
+    }
+}
+
+impl Solver for AStar {
+    fn init(maze: &mut Maze) -> AStar {
+        AStar::with_heuristic(maze, Heuristic::Manhattan)
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        // Skip entries of cells that were closed since they were pushed
+        let entry = loop {
+            match self.best_entry() {
+                Some(i) => {
+                    let entry = self.open.remove(i);
+                    if !maze.get_cell(entry.cell.0, entry.cell.1).is_visited() {
+                        break entry;
+                    }
+                }
+                None => {
+                    self.done = true;
+                    return StepOutcome::Done;
+                }
+            }
+        };
+        let (x, y) = entry.cell;
+        maze.get_cell_mut(x, y).visit(entry.g);
+        self.head = Some((x, y));
+        self.expanded += 1;
+
+        if (x, y) == self.goal {
+            let path = reconstruct_path(&self.parents, self.goal);
+            self.path_cost = path_cost(maze, &path);
+            self.path = Some(path);
+            self.done = true;
+            return StepOutcome::Done;
+        }
+
+        for (nx, ny) in maze.open_neighbors(x, y) {
+            if maze.get_cell(nx, ny).is_visited() {
+                continue;
+            }
+            let g = entry.g + maze.cost(nx, ny);
+            let known = self.g_costs[ny as usize][nx as usize];
+            if known.is_none_or(|known| g < known) {
+                self.g_costs[ny as usize][nx as usize] = Some(g);
+                self.parents[ny as usize][nx as usize] = Some((x, y));
+                self.open.push(OpenEntry {
+                    cell: (nx, ny),
+                    g,
+                    f: g as f64 + self.heuristic.estimate((nx, ny), self.goal),
+                });
+            }
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        // Open cells show their f cost, closed cells their g cost
+        let mut labels: Vec<((i32, i32), i32)> = Vec::new();
+        for (y, row) in self.g_costs.iter().enumerate() {
+            for (x, g) in row.iter().enumerate() {
+                if let Some(g) = g {
+                    labels.push(((x as i32, y as i32), *g));
+                }
+            }
+        }
+        for entry in &self.open {
+            labels.push((entry.cell, entry.f.round() as i32));
+        }
+
+        let mut status =
+            search_status(self.expanded, self.done, self.path.as_ref(), self.path_cost);
+        status.push_str(&format!(
+            "\nOpen: {}, closed: {}",
+            self.open.len(),
+            self.expanded
+        ));
+        if let Some((x, y)) = self.head {
+            let g = self.g_costs[y as usize][x as usize].unwrap_or(0);
+            let h = self.heuristic.estimate((x, y), self.goal);
+            status.push_str(&format!(
+                "\nHead ({}, {}): g {}, h {:.1}, f {:.1}",
+                x,
+                y,
+                g,
+                h,
+                g as f64 + h
+            ));
+        }
+        status.push_str("\nOpen cells show f, closed cells show g");
+
+        Highlights {
+            head: self.head,
+            frontier: self.open.iter().map(|entry| entry.cell).collect(),
+            path: self.path.clone().unwrap_or_default(),
+            labels,
+            status,
+            ..Highlights::default()
+        }
+    }
+}
+
+// Move the end of `trail` to `cell`, dropping the loop when the cell is already on it
+fn extend_trail(trail: &mut Vec<(i32, i32)>, cell: (i32, i32)) {
+    match trail.iter().position(|&c| c == cell) {
+        Some(i) => trail.truncate(i + 1),
+        None => trail.push(cell),
+    }
+}
+
+/// Which hand the wall follower keeps on the wall
+#[derive(Clone, Copy)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Wall follower.
+/// An agent walks with one hand on the wall: it turns towards that hand when
+/// it can, otherwise goes straight, turns away, or turns back. It reaches the
+/// goal in perfect mazes, but may circle forever around a loop, which is
+/// detected by coming back to the same cell with the same facing.
+pub struct WallFollower {
+    hand: Hand,
+    position: (i32, i32),
+    facing: Direction,
+    // Walked cells from the start, without the dead ends walked back from
+    trail: Vec<(i32, i32)>,
+    // Facings already seen in each cell, indexed by [y][x][Direction::index]
+    seen: Vec<Vec<[bool; 4]>>,
+    goal: (i32, i32),
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
+}
+
+impl WallFollower {
+    pub fn with_hand(maze: &mut Maze, hand: Hand) -> WallFollower {
+        let (x, y) = maze.start;
+        maze.get_cell_mut(x, y).visit(0);
+        WallFollower {
+            hand,
+            position: maze.start,
+            facing: Direction::South,
+            trail: vec![maze.start],
+            seen: vec![vec![[false; 4]; maze.width]; maze.height],
+            goal: maze.goal,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: maze.start == maze.goal,
+        }
+    }
+}
+
+impl Solver for WallFollower {
+    fn init(maze: &mut Maze) -> WallFollower {
+        WallFollower::with_hand(maze, Hand::Right)
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.done {
+            return StepOutcome::Done;
+        }
+        let (x, y) = self.position;
+        if self.seen[y as usize][x as usize][self.facing.index()] {
+            // Walking in circles, the goal is not reachable this way
+            self.done = true;
+            return StepOutcome::Done;
+        }
+        self.seen[y as usize][x as usize][self.facing.index()] = true;
+
+        // Directions to try: towards the hand, straight, away, back
+        let facing = self.facing;
+        let turns = match self.hand {
+            Hand::Right => [
+                facing.turn_right(),
+                facing,
+                facing.turn_left(),
+                facing.opposite(),
+            ],
+            Hand::Left => [
+                facing.turn_left(),
+                facing,
+                facing.turn_right(),
+                facing.opposite(),
+            ],
+        };
+        let Some((facing, next)) = turns.into_iter().find_map(|d| {
+            maze.passage(self.position.0, self.position.1, d)
+                .map(|next| (d, next))
+        }) else {
+            // Walled in
+            self.done = true;
+            return StepOutcome::Done;
+        };
+        self.facing = facing;
+        self.position = next;
+        self.expanded += 1;
+        maze.get_cell_mut(next.0, next.1)
+            .visit(self.expanded as i32);
+        extend_trail(&mut self.trail, next);
+
+        if next == self.goal {
+            self.path_cost = path_cost(maze, &self.trail);
+            self.path = Some(self.trail.clone());
+            self.done = true;
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        Highlights {
+            head: Some(self.position),
+            facing: Some(self.facing),
+            stack: self.trail.clone(),
+            path: self.path.clone().unwrap_or_default(),
+            status: search_status(self.expanded, self.done, self.path.as_ref(), self.path_cost),
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Trémaux's algorithm.
+/// An agent marks each passage every time it walks through it. Arriving at an
+/// already visited cell through a fresh passage it turns back, otherwise it
+/// prefers unmarked passages, and never takes a passage marked twice. The
+/// passages marked once always lead from the start to the agent, and form the
+/// solution once the goal is reached.
+pub struct Tremaux {
+    position: (i32, i32),
+    // Direction the agent came from, None at the start
+    back: Option<Direction>,
+    // Whether the current cell had been visited before this arrival
+    revisit: bool,
+    // Marks on the passage leaving each cell in each direction, indexed by [y][x][Direction::index]
+    marks: Vec<Vec<[u8; 4]>>,
+    // Cells joined by passages marked once, from the start to the agent
+    trail: Vec<(i32, i32)>,
+    goal: (i32, i32),
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
+}
+
+impl Tremaux {
+    fn mark(&self, (x, y): (i32, i32), d: Direction) -> u8 {
+        self.marks[y as usize][x as usize][d.index()]
+    }
+
+    // Marks per count, for the info panel
+    fn count_marks(&self, count: u8) -> usize {
+        // Each passage is counted from both of its cells
+        self.marks
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|&&m| m == count)
+            .count()
+            / 2
+    }
+}
+
+impl Solver for Tremaux {
+    fn init(maze: &mut Maze) -> Tremaux {
+        let (x, y) = maze.start;
+        maze.get_cell_mut(x, y).visit(0);
+        Tremaux {
+            position: maze.start,
+            back: None,
+            revisit: false,
+            marks: vec![vec![[0; 4]; maze.width]; maze.height],
+            trail: vec![maze.start],
+            goal: maze.goal,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: maze.start == maze.goal,
+        }
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        if self.done {
+            return StepOutcome::Done;
+        }
+        let position = self.position;
+        let open: Vec<Direction> = Direction::ALL
+            .into_iter()
+            .filter(|&d| maze.passage(position.0, position.1, d).is_some())
+            .collect();
+
+        let direction = match self.back {
+            // Back at a known cell through a fresh passage, go back the same way
+            Some(back) if self.revisit && self.mark(position, back) == 1 => Some(back),
+            back => open
+                .iter()
+                .copied()
+                .find(|&d| Some(d) != back && self.mark(position, d) == 0)
+                .or_else(|| {
+                    open.iter()
+                        .copied()
+                        .filter(|&d| self.mark(position, d) < 2)
+                        .min_by_key(|&d| self.mark(position, d))
+                }),
+        };
+        let Some(d) = direction else {
+            // Every passage is marked twice, the goal is not reachable
+            self.done = true;
+            return StepOutcome::Done;
+        };
+
+        let (nx, ny) = maze.passage(position.0, position.1, d).unwrap();
+        self.marks[position.1 as usize][position.0 as usize][d.index()] += 1;
+        self.marks[ny as usize][nx as usize][d.opposite().index()] += 1;
+        self.revisit = maze.get_cell(nx, ny).is_visited();
+        self.back = Some(d.opposite());
+        self.position = (nx, ny);
+        self.expanded += 1;
+        maze.get_cell_mut(nx, ny).visit(self.expanded as i32);
+        extend_trail(&mut self.trail, (nx, ny));
+
+        if (nx, ny) == self.goal {
+            self.path_cost = path_cost(maze, &self.trail);
+            self.path = Some(self.trail.clone());
+            self.done = true;
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        let mut status =
+            search_status(self.expanded, self.done, self.path.as_ref(), self.path_cost);
+        status.push_str(&format!(
+            "\nPassages marked once: {}, twice: {}",
+            self.count_marks(1),
+            self.count_marks(2)
+        ));
+        Highlights {
+            head: Some(self.position),
+            facing: self.back.map(|back| back.opposite()),
+            stack: self.trail.clone(),
+            path: self.path.clone().unwrap_or_default(),
+            status,
+            ..Highlights::default()
+        }
+    }
+}
+
+/// Dead-end filling.
+/// Fills one dead end per step, a cell other than the start and the goal with
+/// a single open passage to an unfilled cell. Filling a cell may turn its
+/// neighbour into a new dead end. When none is left, the unfilled cells are
+/// the solution, plus any loop of the maze. Filled cells are marked visited.
+pub struct DeadEndFilling {
+    dead_ends: Vec<(i32, i32)>,
+    start: (i32, i32),
+    goal: (i32, i32),
+    head: Option<(i32, i32)>,
+    path: Option<Vec<(i32, i32)>>,
+    path_cost: i32,
+    expanded: usize,
+    done: bool,
+}
+
+impl DeadEndFilling {
+    // Unfilled neighbours reachable from `cell`
+    fn open_neighbors(maze: &Maze, cell: (i32, i32)) -> Vec<(i32, i32)> {
+        maze.open_neighbors(cell.0, cell.1)
+            .into_iter()
+            .filter(|&(x, y)| !maze.get_cell(x, y).is_visited())
+            .collect()
+    }
+
+    fn is_dead_end(&self, maze: &Maze, cell: (i32, i32)) -> bool {
+        cell != self.start
+            && cell != self.goal
+            && maze.is_inside(cell.0, cell.1)
+            && !maze.get_cell(cell.0, cell.1).is_visited()
+            && DeadEndFilling::open_neighbors(maze, cell).len() <= 1
+    }
+
+    // Walk the unfilled cells from the start to the goal
+    fn find_path(&self, maze: &Maze) -> Option<Vec<(i32, i32)>> {
+        let mut parents: Parents = vec![vec![None; maze.width]; maze.height];
+        let mut seen = vec![vec![false; maze.width]; maze.height];
+        let mut queue = VecDeque::from([self.start]);
+        seen[self.start.1 as usize][self.start.0 as usize] = true;
+        while let Some(cell) = queue.pop_front() {
+            if cell == self.goal {
+                return Some(reconstruct_path(&parents, self.goal));
+            }
+            for (nx, ny) in DeadEndFilling::open_neighbors(maze, cell) {
+                if !seen[ny as usize][nx as usize] {
+                    seen[ny as usize][nx as usize] = true;
+                    parents[ny as usize][nx as usize] = Some(cell);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Solver for DeadEndFilling {
+    fn init(maze: &mut Maze) -> DeadEndFilling {
+        let mut solver = DeadEndFilling {
+            dead_ends: Vec::new(),
+            start: maze.start,
+            goal: maze.goal,
+            head: None,
+            path: None,
+            path_cost: 0,
+            expanded: 0,
+            done: false,
+        };
+        for y in 0..maze.height as i32 {
+            for x in 0..maze.width as i32 {
+                if solver.is_dead_end(maze, (x, y)) {
+                    solver.dead_ends.push((x, y));
+                }
+            }
+        }
+        solver
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> StepOutcome {
+        // Skip dead ends already filled from another side
+        let cell = loop {
+            match self.dead_ends.pop() {
+                Some(cell) if self.is_dead_end(maze, cell) => break cell,
+                Some(_) => continue,
+                None => {
+                    self.path = self.find_path(maze);
+                    if let Some(path) = &self.path {
+                        self.path_cost = path_cost(maze, path);
+                    }
+                    self.head = None;
+                    self.done = true;
+                    return StepOutcome::Done;
+                }
+            }
+        };
+        let neighbors = DeadEndFilling::open_neighbors(maze, cell);
+        maze.get_cell_mut(cell.0, cell.1)
+            .visit(self.expanded as i32);
+        self.head = Some(cell);
+        self.expanded += 1;
+        for neighbor in neighbors {
+            if self.is_dead_end(maze, neighbor) {
+                self.dead_ends.push(neighbor);
+            }
+        }
+        self.outcome()
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn highlights(&self) -> Highlights {
+        let mut status = format!("Filled: {}", self.expanded);
+        if self.done {
+            status = match &self.path {
+                Some(path) => format!(
+                    "{}, path length: {}, cost: {}",
+                    status,
+                    path.len(),
+                    self.path_cost
+                ),
+                None => format!("{}, no path found", status),
+            };
+        }
+        Highlights {
+            head: self.head,
+            frontier: self.dead_ends.clone(),
+            path: self.path.clone().unwrap_or_default(),
+            status,
+            ..Highlights::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::maze_generation::{
+        self, generate_maze, MazeSettings, RecursiveBacktracker, TerrainMode,
+    };
+
+    // Backtracker maze with loops, so that solvers have several routes to choose from
+    fn braided(seed: u64, terrain: TerrainMode) -> Maze {
+        let settings = MazeSettings {
+            width: 15,
+            height: 9,
+            seed,
+            terrain,
+            braid: 60,
+            sparseness: 0,
+            mask: None,
+        };
+        generate_maze(
+            "Recursive Backtracker",
+            maze_generation::create::<RecursiveBacktracker>,
+            settings,
+        )
+    }
+
+    // Step a solver on a copy of `maze` until it is done, like `run_solver`,
+    // returns the path it found
+    fn solve(create: SolverConstructor, maze: &Maze) -> Vec<(i32, i32)> {
+        let mut maze = maze.clone();
+        maze.reset_visited();
+        let mut solver = create(&mut maze);
+        for _ in 0..16 * maze.width * maze.height {
+            if solver.step(&mut maze) == StepOutcome::Done {
+                return solver.highlights().path;
+            }
+        }
+        panic!("the solver did not finish");
+    }
+
+    // Start to goal through open passages only
+    fn assert_valid_path(maze: &Maze, path: &[(i32, i32)]) {
+        assert_eq!(path.first(), Some(&maze.start));
+        assert_eq!(path.last(), Some(&maze.goal));
+        for pair in path.windows(2) {
+            let (x, y) = pair[0];
+            assert!(
+                maze.open_neighbors(x, y).contains(&pair[1]),
+                "no passage from {:?} to {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn bfs_finds_a_shortest_path() {
+        for seed in 0..5 {
+            let maze = braided(seed, TerrainMode::Uniform);
+            let path = solve(create::<Bfs>, &maze);
+            assert_valid_path(&maze, &path);
+            assert_eq!(Some(path.len()), maze.analyze().solution.map(|p| p.len()));
+            assert_valid_path(&maze, &solve(create::<Dfs>, &maze));
+        }
+    }
+
+    #[test]
+    fn a_star_matches_dijkstra_on_terrain() {
+        for seed in 0..5 {
+            let maze = braided(seed, TerrainMode::Random);
+            let dijkstra = solve(|maze| Box::new(AStar::dijkstra(maze)), &maze);
+            assert_valid_path(&maze, &dijkstra);
+            let expected = path_cost(&maze, &dijkstra);
+            // The fewest steps are not always the cheapest on terrain
+            assert!(expected <= path_cost(&maze, &solve(create::<Bfs>, &maze)));
+            let a_stars: [SolverConstructor; 3] = [
+                |maze| Box::new(AStar::with_heuristic(maze, Heuristic::Manhattan)),
+                |maze| Box::new(AStar::with_heuristic(maze, Heuristic::Euclidean)),
+                |maze| Box::new(AStar::with_heuristic(maze, Heuristic::Chebyshev)),
+            ];
+            for create in a_stars {
+                let path = solve(create, &maze);
+                assert_valid_path(&maze, &path);
+                assert_eq!(path_cost(&maze, &path), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn bidirectional_bfs_matches_bfs() {
+        for seed in 0..5 {
+            let maze = braided(seed, TerrainMode::Uniform);
+            let path = solve(create::<BidirectionalBfs>, &maze);
+            assert_valid_path(&maze, &path);
+            assert_eq!(path.len(), solve(create::<Bfs>, &maze).len());
+            assert_eq!(Some(path.len()), maze.analyze().solution.map(|p| p.len()));
+        }
+    }
+
+    #[test]
+    fn tremaux_and_dead_end_filling_reach_the_goal() {
+        for seed in 0..5 {
+            let maze = braided(seed, TerrainMode::Uniform);
+            assert_valid_path(&maze, &solve(create::<Tremaux>, &maze));
+            assert_valid_path(&maze, &solve(create::<DeadEndFilling>, &maze));
+        }
+    }
+
+    #[test]
+    fn wall_follower_gives_up_on_an_island() {
+        // Every inner corner of an open maze is a free-standing pillar. Starting
+        // in the middle, the right hand follows the pillar to its north-west
+        // and comes back to the start facing the same way
+        let mut maze = Maze::new_open(5, 5);
+        maze.start = (2, 2);
+        maze.goal = (4, 4);
+        let right: SolverConstructor = |maze| Box::new(WallFollower::with_hand(maze, Hand::Right));
+        assert!(solve(right, &maze).is_empty());
+        assert!(maze.analyze().solution.is_some());
+
+        // On braided mazes it either reaches the goal or stops
+        for seed in 0..5 {
+            let maze = braided(seed, TerrainMode::Uniform);
+            let path = solve(right, &maze);
+            if !path.is_empty() {
+                assert_valid_path(&maze, &path);
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 // Maze data structure
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::data::maze_mask::Mask;
 
 pub const EMPTY_CHAR: char = ' ';
 pub const WALL_CHAR: char = '#';
@@ -8,12 +11,132 @@ pub const VISITED_CHAR: char = '.';
 pub const GOAL_CHAR: char = 'G';
 pub const START_CHAR: char = 'S';
 
+// Distance in cells between two points of the noise lattice
+const NOISE_SPACING: usize = 4;
+
+/// Side of a cell. `Cell::walls` is indexed by `Direction::index`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// Clockwise from north
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn index(&self) -> usize {
+        match self {
+            Direction::North => 0,
+            Direction::East => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        Direction::ALL[(self.index() + 2) % 4]
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        Direction::ALL[(self.index() + 1) % 4]
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        Direction::ALL[(self.index() + 3) % 4]
+    }
+
+    /// (dx, dy) to the neighbour on this side, y grows downward
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    /// Side of `from` that `to` is on, None if the cells are not adjacent
+    pub fn between(from: (i32, i32), to: (i32, i32)) -> Option<Direction> {
+        let offset = (to.0 - from.0, to.1 - from.1);
+        Direction::ALL.into_iter().find(|d| d.offset() == offset)
+    }
+}
+
+/// Kind of ground covering a cell, which sets the cost of stepping onto it
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Terrain {
+    Road,
+    Grass,
+    Mud,
+    Water,
+}
+
+impl Terrain {
+    pub fn cost(&self) -> i32 {
+        match self {
+            Terrain::Road => 1,
+            Terrain::Grass => 2,
+            Terrain::Mud => 5,
+            Terrain::Water => 10,
+        }
+    }
+
+    // Terrain for a level in [0, 1), low levels are the cheapest
+    fn from_level(level: f64) -> Terrain {
+        if level < 0.4 {
+            Terrain::Road
+        } else if level < 0.7 {
+            Terrain::Grass
+        } else if level < 0.9 {
+            Terrain::Mud
+        } else {
+            Terrain::Water
+        }
+    }
+}
+
+/// Generator, seed and post-processing a maze was built with, so that it can be reproduced
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Provenance {
+    pub generator: String,
+    pub seed: u64,
+    // Percentages from `MazeSettings`, missing in files saved before they existed
+    #[serde(default)]
+    pub braid: u8,
+    #[serde(default)]
+    pub sparseness: u8,
+}
+
+/// Result of the last solver run on a maze
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SolverRun {
+    pub solver: String,
+    // Empty when no path was found
+    pub path: Vec<(i32, i32)>,
+    pub path_cost: i32,
+    // Cells in the order the solver worked on them
+    pub visit_order: Vec<(i32, i32)>,
+}
+
 pub struct Maze {
     pub width: usize,
     pub height: usize,
     pub cells: Vec<Vec<Cell>>,
     pub start: (i32, i32),
     pub goal: (i32, i32),
+    // None for mazes edited by hand or loaded without this information
+    pub origin: Option<Provenance>,
+    pub last_run: Option<SolverRun>,
+    // Cells outside of the shape of the maze, None for a full rectangle
+    pub mask: Option<Mask>,
 }
 
 impl Maze {
@@ -34,7 +157,45 @@ impl Maze {
             cells,
             start: (1, 1),
             goal: (width as i32 - 2, height as i32 - 2),
+            origin: None,
+            last_run: None,
+            mask: None,
+        }
+    }
+
+    /// Create a maze without any inner wall, for wall-adding algorithms
+    pub fn new_open(width: usize, height: usize) -> Maze {
+        let mut maze = Maze::new(width, height);
+        maze.open_all();
+        maze
+    }
+
+    /// Remove every inner wall, walls around masked cells are kept
+    pub fn open_all(&mut self) {
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if x < self.width as i32 - 1 {
+                    self.remove_wall(x, y, x + 1, y);
+                }
+                if y < self.height as i32 - 1 {
+                    self.remove_wall(x, y, x, y + 1);
+                }
+            }
+        }
+    }
+
+    /// Append a fresh row of cells at the bottom of the maze
+    pub fn push_row(&mut self) {
+        let y = self.height;
+        let row = (0..self.width)
+            .map(|x| Cell::new(x as i32 + y as i32, EMPTY_CHAR))
+            .collect();
+        self.cells.push(row);
+        if let Some(mask) = &mut self.mask {
+            mask.push_row();
         }
+        self.height += 1;
+        self.goal = (self.width as i32 - 2, self.height as i32 - 2);
     }
 
     pub fn get_cell(&self, x: i32, y: i32) -> &Cell {
@@ -45,27 +206,71 @@ impl Maze {
         &mut self.cells[y as usize][x as usize]
     }
 
-    pub fn get_random_cell(&self) -> (i32, i32) {
-        let x = rand::thread_rng().gen_range(0..self.width as i32);
-        let y = rand::thread_rng().gen_range(0..self.height as i32);
+    /// Random cell inside the mask
+    pub fn get_random_cell<R: Rng>(&self, rng: &mut R) -> (i32, i32) {
+        if self.mask.is_some() {
+            let cells = self.inside_cells();
+            return cells[rng.gen_range(0..cells.len())];
+        }
+        let x = rng.gen_range(0..self.width as i32);
+        let y = rng.gen_range(0..self.height as i32);
         (x, y)
     }
 
-    pub fn get_neighbors(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
-        let mut neighbors = Vec::new();
-        if x > 0 {
-            neighbors.push((x - 1, y));
-        }
-        if x < self.width as i32 - 1 {
-            neighbors.push((x + 1, y));
-        }
-        if y > 0 {
-            neighbors.push((x, y - 1));
+    /// Whether (x, y) is a cell of the maze, in bounds and not masked
+    pub fn is_inside(&self, x: i32, y: i32) -> bool {
+        let in_bounds = x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32;
+        in_bounds
+            && self
+                .mask
+                .as_ref()
+                .is_none_or(|mask| mask.is_inside(x as usize, y as usize))
+    }
+
+    /// Cells that are not masked, in reading order
+    pub fn inside_cells(&self) -> Vec<(i32, i32)> {
+        (0..self.height as i32)
+            .flat_map(|y| (0..self.width as i32).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_inside(x, y))
+            .collect()
+    }
+
+    pub fn inside_count(&self) -> usize {
+        match &self.mask {
+            Some(mask) => mask.inside_count(),
+            None => self.width * self.height,
         }
-        if y < self.height as i32 - 1 {
-            neighbors.push((x, y + 1));
+    }
+
+    pub fn get_neighbors(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        // West and east first, generators pick from this list so the order
+        // must stay the same for a seed to keep giving the same maze
+        [
+            Direction::West,
+            Direction::East,
+            Direction::North,
+            Direction::South,
+        ]
+        .into_iter()
+        .filter_map(|d| self.neighbor(x, y, d))
+        .collect()
+    }
+
+    /// Neighbours reachable from (x, y) without crossing a wall,
+    /// in the order of `get_neighbors`
+    pub fn open_neighbors(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
+        self.get_neighbors(x, y)
+            .into_iter()
+            .filter(|&(nx, ny)| !self.has_wall(x, y, nx, ny))
+            .collect()
+    }
+
+    /// Neighbour of (x, y) in direction `d` if no wall is in the way
+    pub fn passage(&self, x: i32, y: i32, d: Direction) -> Option<(i32, i32)> {
+        if self.get_cell(x, y).has_wall(d) {
+            return None;
         }
-        neighbors
+        self.neighbor(x, y, d)
     }
 
     pub fn get_walls(&self) -> Vec<(i32, i32)> {
@@ -80,32 +285,204 @@ impl Maze {
         walls
     }
 
-    pub fn get_neighbor(&self, x: i32, y: i32) -> (i32, i32) {
+    pub fn get_neighbor<R: Rng>(&self, x: i32, y: i32, rng: &mut R) -> (i32, i32) {
         let neighbors = self.get_neighbors(x, y);
-        let index = rand::thread_rng().gen_range(0..neighbors.len());
+        let index = rng.gen_range(0..neighbors.len());
         neighbors[index]
     }
 
-    pub fn remove_wall(&mut self, x: i32, y: i32, nx: i32, ny: i32) {
-        let dx = nx - x;
-        let dy = ny - y;
-        if dx == 1 {
-            self.get_cell_mut(x, y).walls[1] = false;
-            self.get_cell_mut(nx, ny).walls[3] = false;
-        } else if dx == -1 {
-            self.get_cell_mut(x, y).walls[3] = false;
-            self.get_cell_mut(nx, ny).walls[1] = false;
-        } else if dy == 1 {
-            self.get_cell_mut(x, y).walls[2] = false;
-            self.get_cell_mut(nx, ny).walls[0] = false;
-        } else if dy == -1 {
-            self.get_cell_mut(x, y).walls[0] = false;
-            self.get_cell_mut(nx, ny).walls[2] = false;
+    /// Neighbour of (x, y) in direction `d`, None outside of the maze or of its mask
+    pub fn neighbor(&self, x: i32, y: i32, d: Direction) -> Option<(i32, i32)> {
+        let (dx, dy) = d.offset();
+        let (nx, ny) = (x + dx, y + dy);
+        (self.is_inside(x, y) && self.is_inside(nx, ny)).then_some((nx, ny))
+    }
+
+    // Set the wall between two adjacent cells on both of its sides.
+    // Walls of masked cells always stay up
+    fn set_wall(&mut self, x: i32, y: i32, nx: i32, ny: i32, present: bool) {
+        let Some(d) = Direction::between((x, y), (nx, ny)) else {
+            return;
+        };
+        if !self.is_inside(x, y) || !self.is_inside(nx, ny) {
+            return;
         }
+        self.get_cell_mut(x, y).set_wall(d, present);
+        self.get_cell_mut(nx, ny).set_wall(d.opposite(), present);
+    }
 
+    pub fn remove_wall(&mut self, x: i32, y: i32, nx: i32, ny: i32) {
+        self.set_wall(x, y, nx, ny, false);
         self.get_cell_mut(x, y).c = EMPTY_CHAR;
     }
 
+    pub fn add_wall(&mut self, x: i32, y: i32, nx: i32, ny: i32) {
+        self.set_wall(x, y, nx, ny, true);
+    }
+
+    /// Doubled-resolution grid of the maze, (2 * width + 1) x (2 * height + 1).
+    /// Cell (x, y) sits at [2 * y + 1][2 * x + 1], the wall between two cells
+    /// halfway between them, and the outer border is always closed.
+    /// Walls are `WALL_CHAR` and cells and open passages `PATH_CHAR`.
+    /// Masked cells are filled with `WALL_CHAR`.
+    pub fn wall_grid(&self) -> Vec<Vec<char>> {
+        let mut grid = vec![vec![WALL_CHAR; self.width * 2 + 1]; self.height * 2 + 1];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (gx, gy) = (x * 2 + 1, y * 2 + 1);
+                if !self.is_inside(x as i32, y as i32) {
+                    continue;
+                }
+                grid[gy][gx] = PATH_CHAR;
+                for d in Direction::ALL {
+                    if self.passage(x as i32, y as i32, d).is_some() {
+                        let (dx, dy) = d.offset();
+                        grid[(gy as i32 + dy) as usize][(gx as i32 + dx) as usize] = PATH_CHAR;
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    /// Whether a wall stands between (x, y) and its neighbour (nx, ny)
+    pub fn has_wall(&self, x: i32, y: i32, nx: i32, ny: i32) -> bool {
+        match Direction::between((x, y), (nx, ny)) {
+            Some(d) => self.get_cell(x, y).has_wall(d),
+            None => true,
+        }
+    }
+
+    pub fn toggle_wall(&mut self, x: i32, y: i32, nx: i32, ny: i32) {
+        if self.has_wall(x, y, nx, ny) {
+            self.remove_wall(x, y, nx, ny);
+        } else {
+            self.add_wall(x, y, nx, ny);
+        }
+    }
+
+    /// Remove every wall inside the rectangle between two corner cells.
+    /// Walls on the edge of the rectangle are kept.
+    pub fn clear_region(&mut self, a: (i32, i32), b: (i32, i32)) {
+        let (x0, y0, x1, y1) = (a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                if x < x1 {
+                    self.remove_wall(x, y, x + 1, y);
+                }
+                if y < y1 {
+                    self.remove_wall(x, y, x, y + 1);
+                }
+            }
+        }
+    }
+
+    /// Replace the inside of the rectangle between two corner cells by a random
+    /// perfect maze, carved with a depth-first backtracker.
+    /// Walls on the edge of the rectangle are kept, and so are masked cells.
+    pub fn randomize_region<R: Rng>(&mut self, a: (i32, i32), b: (i32, i32), rng: &mut R) {
+        let (x0, y0, x1, y1) = (a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                if x < x1 {
+                    self.add_wall(x, y, x + 1, y);
+                }
+                if y < y1 {
+                    self.add_wall(x, y, x, y + 1);
+                }
+            }
+        }
+
+        let inside = |(x, y): (i32, i32)| x >= x0 && x <= x1 && y >= y0 && y <= y1;
+        let width = (x1 - x0 + 1) as usize;
+        let mut carved = vec![false; width * (y1 - y0 + 1) as usize];
+        let index = |(x, y): (i32, i32)| (y - y0) as usize * width + (x - x0) as usize;
+        // Masked cells count as carved so that the backtracker never enters them
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                carved[index((x, y))] = !self.is_inside(x, y);
+            }
+        }
+
+        let mut first = (rng.gen_range(x0..=x1), rng.gen_range(y0..=y1));
+        if carved[index(first)] {
+            match (y0..=y1)
+                .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+                .find(|&cell| !carved[index(cell)])
+            {
+                Some(cell) => first = cell,
+                None => return,
+            }
+        }
+        carved[index(first)] = true;
+        let mut stack = vec![first];
+        while let Some(&(x, y)) = stack.last() {
+            let candidates: Vec<(i32, i32)> = self
+                .get_neighbors(x, y)
+                .into_iter()
+                .filter(|&n| inside(n) && !carved[index(n)])
+                .collect();
+            if candidates.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (nx, ny) = candidates[rng.gen_range(0..candidates.len())];
+            self.remove_wall(x, y, nx, ny);
+            carved[index((nx, ny))] = true;
+            stack.push((nx, ny));
+        }
+    }
+
+    /// Clear the visited flags left by a previous algorithm
+    pub fn reset_visited(&mut self) {
+        for row in self.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.visited = false;
+            }
+        }
+    }
+
+    /// Cost of stepping onto the cell at (x, y)
+    pub fn cost(&self, x: i32, y: i32) -> i32 {
+        self.get_cell(x, y).terrain.cost()
+    }
+
+    /// Give every cell an independent random terrain, roads being the most common
+    pub fn paint_terrain_random<R: Rng>(&mut self, rng: &mut R) {
+        for row in self.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.terrain = Terrain::from_level(rng.gen::<f64>());
+            }
+        }
+    }
+
+    /// Paint terrain from value noise: random levels on a coarse lattice are
+    /// interpolated across the cells in between, giving patches of terrain
+    pub fn paint_terrain_noise<R: Rng>(&mut self, rng: &mut R) {
+        let lattice_width = self.width / NOISE_SPACING + 2;
+        let lattice_height = self.height / NOISE_SPACING + 2;
+        let lattice: Vec<Vec<f64>> = (0..lattice_height)
+            .map(|_| (0..lattice_width).map(|_| rng.gen::<f64>()).collect())
+            .collect();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (lx, fx) = (
+                    x / NOISE_SPACING,
+                    (x % NOISE_SPACING) as f64 / NOISE_SPACING as f64,
+                );
+                let (ly, fy) = (
+                    y / NOISE_SPACING,
+                    (y % NOISE_SPACING) as f64 / NOISE_SPACING as f64,
+                );
+                let top = lattice[ly][lx] * (1.0 - fx) + lattice[ly][lx + 1] * fx;
+                let bottom = lattice[ly + 1][lx] * (1.0 - fx) + lattice[ly + 1][lx + 1] * fx;
+                let level = top * (1.0 - fy) + bottom * fy;
+                self.cells[y][x].terrain = Terrain::from_level(level);
+            }
+        }
+    }
+
     pub fn set_cell(&mut self, x: i32, y: i32, value: i32) {
         self.cells[y as usize][x as usize].value = value;
     }
@@ -125,15 +502,21 @@ impl Maze {
             cells,
             start: self.start,
             goal: self.goal,
+            origin: self.origin.clone(),
+            last_run: self.last_run.clone(),
+            mask: self.mask.clone(),
         }
     }
 }
 
 pub struct Cell {
+    // Indexed by `Direction::index`
     pub walls: [bool; 4],
     pub visited: bool,
+    // Scratch value set by the algorithm that visited the cell
     pub value: i32,
     pub c: char,
+    pub terrain: Terrain,
 }
 
 impl Cell {
@@ -143,6 +526,7 @@ impl Cell {
             visited: false,
             value,
             c,
+            terrain: Terrain::Road,
         }
     }
 
@@ -169,19 +553,164 @@ impl Cell {
             visited: self.visited,
             value: self.value,
             c: self.c,
+            terrain: self.terrain,
         }
     }
 
-    pub fn has_wall_north(&self) -> bool {
-        self.walls[0]
+    pub fn has_wall(&self, d: Direction) -> bool {
+        self.walls[d.index()]
     }
-    pub fn has_wall_south(&self) -> bool {
-        self.walls[1]
+
+    pub fn set_wall(&mut self, d: Direction, present: bool) {
+        self.walls[d.index()] = present;
+    }
+}
+
+/// Disjoint-set (union-find) over `0..n`, with path compression and union by size.
+/// Used by Kruskal's algorithm and the connectivity checks to know whether
+/// two cells are already connected.
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    count: usize,
+}
+
+impl DisjointSet {
+    pub fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            count: n,
+        }
+    }
+
+    pub fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Path compression
+        let mut i = i;
+        while self.parent[i] != root {
+            let next = self.parent[i];
+            self.parent[i] = root;
+            i = next;
+        }
+        root
+    }
+
+    /// Merge the sets containing `a` and `b`.
+    /// Returns false if they were already in the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let mut a = self.find(a);
+        let mut b = self.find(b);
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.count -= 1;
+        true
+    }
+
+    /// Number of disjoint sets remaining
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop::sample::select(Direction::ALL.to_vec())
     }
-    pub fn has_wall_west(&self) -> bool {
-        self.walls[2]
+
+    // A maze size with a cell inside it
+    fn maze_and_cell() -> impl Strategy<Value = (usize, usize, i32, i32)> {
+        (1usize..12, 1usize..12).prop_flat_map(|(width, height)| {
+            (Just(width), Just(height), 0..width as i32, 0..height as i32)
+        })
+    }
+
+    fn walls(maze: &Maze) -> Vec<[bool; 4]> {
+        maze.cells.iter().flatten().map(|cell| cell.walls).collect()
     }
-    pub fn has_wall_east(&self) -> bool {
-        self.walls[3]
+
+    proptest! {
+        #[test]
+        fn opposite_is_an_involution(d in direction()) {
+            prop_assert_eq!(d.opposite().opposite(), d);
+            prop_assert_ne!(d.opposite(), d);
+            let (dx, dy) = d.offset();
+            prop_assert_eq!(d.opposite().offset(), (-dx, -dy));
+        }
+
+        #[test]
+        fn turns_undo_each_other(d in direction()) {
+            prop_assert_eq!(d.turn_right().turn_left(), d);
+            prop_assert_eq!(d.turn_right().turn_right(), d.opposite());
+        }
+
+        #[test]
+        fn between_inverts_offset(x in -20i32..20, y in -20i32..20, d in direction()) {
+            let (dx, dy) = d.offset();
+            prop_assert_eq!(Direction::between((x, y), (x + dx, y + dy)), Some(d));
+            prop_assert_eq!(Direction::between((x, y), (x, y)), None);
+            prop_assert_eq!(Direction::between((x, y), (x + 2 * dx, y + 2 * dy)), None);
+        }
+
+        #[test]
+        fn carving_opens_the_shared_edge_on_both_sides(
+            (width, height, x, y) in maze_and_cell(),
+            d in direction(),
+        ) {
+            let mut maze = Maze::new(width, height);
+            let Some((nx, ny)) = maze.neighbor(x, y, d) else {
+                return Ok(());
+            };
+            let before = walls(&maze);
+            maze.remove_wall(x, y, nx, ny);
+
+            prop_assert!(!maze.get_cell(x, y).has_wall(d));
+            prop_assert!(!maze.get_cell(nx, ny).has_wall(d.opposite()));
+            prop_assert!(!maze.has_wall(x, y, nx, ny));
+            prop_assert!(!maze.has_wall(nx, ny, x, y));
+
+            // Every other wall is untouched
+            let changed = before
+                .iter()
+                .zip(walls(&maze))
+                .map(|(a, b)| a.iter().zip(b).filter(|(a, b)| *a != b).count())
+                .sum::<usize>();
+            prop_assert_eq!(changed, 2);
+
+            // The passage shows up halfway between the cells
+            let grid = maze.wall_grid();
+            prop_assert_eq!(grid[(y + ny + 1) as usize][(x + nx + 1) as usize], PATH_CHAR);
+
+            maze.add_wall(nx, ny, x, y);
+            prop_assert_eq!(walls(&maze), before);
+        }
+
+        #[test]
+        fn neighbor_stays_inside(
+            (width, height, x, y) in maze_and_cell(),
+            d in direction(),
+        ) {
+            let maze = Maze::new(width, height);
+            let (dx, dy) = d.offset();
+            let expected = (x + dx, y + dy);
+            let inside = expected.0 >= 0
+                && expected.1 >= 0
+                && expected.0 < width as i32
+                && expected.1 < height as i32;
+            prop_assert_eq!(maze.neighbor(x, y, d), inside.then_some(expected));
+        }
     }
 }
@@ -0,0 +1,228 @@
+// Measures of the texture of a maze, to compare generators by numbers rather
+// than by eye. Everything is computed on the passage graph from `maze_graph`,
+// counting steps and ignoring terrain costs.
+
+use std::collections::VecDeque;
+
+use crate::data::data_structures::Maze;
+use crate::data::maze_graph::MazeGraph;
+
+/// Steps from a source cell, indexed by [y][x], None where it cannot be reached
+pub type Distances = Vec<Vec<Option<usize>>>;
+
+pub struct MazeAnalysis {
+    /// Cells with a single passage
+    pub dead_ends: usize,
+    /// Cells with three or more passages
+    pub junctions: usize,
+    /// Number of corridors of each length in cells, indexed by length.
+    /// A corridor is a run of cells with exactly two passages.
+    pub corridor_lengths: Vec<usize>,
+    /// Mean number of ways on from a junction, not counting the way in
+    pub branching_factor: f64,
+    /// Share of the cells that lie in corridors. High for mazes of long winding
+    /// passages like the backtracker's, low for bushy ones like Prim's
+    pub river_factor: f64,
+    /// Longest shortest path in the part of the maze reachable from the start,
+    /// found with two BFS passes. Exact on perfect mazes
+    pub longest_path: Vec<(i32, i32)>,
+    /// Shortest path from the start to the goal, None if the goal cannot be reached
+    pub solution: Option<Vec<(i32, i32)>>,
+    /// Distances from the start
+    pub distances: Distances,
+}
+
+impl MazeAnalysis {
+    pub fn corridor_count(&self) -> usize {
+        self.corridor_lengths.iter().sum()
+    }
+
+    pub fn mean_corridor_length(&self) -> f64 {
+        let cells: usize = self
+            .corridor_lengths
+            .iter()
+            .enumerate()
+            .map(|(length, count)| length * count)
+            .sum();
+        cells as f64 / self.corridor_count().max(1) as f64
+    }
+}
+
+// Distance and parent of every node reached by a BFS from `source`
+struct Search {
+    distances: Vec<Option<usize>>,
+    parents: Vec<Option<usize>>,
+}
+
+impl Search {
+    fn run(graph: &MazeGraph, source: usize) -> Search {
+        let mut search = Search {
+            distances: vec![None; graph.node_count()],
+            parents: vec![None; graph.node_count()],
+        };
+        search.distances[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            let distance = search.distances[node].unwrap_or(0);
+            for &next in graph.neighbors(node) {
+                if search.distances[next].is_none() {
+                    search.distances[next] = Some(distance + 1);
+                    search.parents[next] = Some(node);
+                    queue.push_back(next);
+                }
+            }
+        }
+        search
+    }
+
+    // Reached node furthest from the source
+    fn furthest(&self) -> usize {
+        (0..self.distances.len())
+            .max_by_key(|&node| self.distances[node])
+            .unwrap_or(0)
+    }
+
+    // Path from the source to `node`, source first
+    fn path_to(&self, graph: &MazeGraph, node: usize) -> Option<Vec<(i32, i32)>> {
+        self.distances[node]?;
+        let mut path = vec![graph.cell(node)];
+        let mut current = node;
+        while let Some(parent) = self.parents[current] {
+            path.push(graph.cell(parent));
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Maze {
+    pub fn analyze(&self) -> MazeAnalysis {
+        let graph = self.to_graph();
+        let degrees: Vec<usize> = (0..graph.node_count())
+            .map(|node| graph.neighbors(node).len())
+            .collect();
+
+        let dead_ends = degrees.iter().filter(|&&degree| degree == 1).count();
+        let junctions: Vec<usize> = degrees
+            .iter()
+            .copied()
+            .filter(|&degree| degree >= 3)
+            .collect();
+        let branching_factor = junctions.iter().map(|degree| degree - 1).sum::<usize>() as f64
+            / junctions.len().max(1) as f64;
+
+        let corridor_lengths = corridor_lengths(&graph, &degrees);
+        let corridor_cells = degrees.iter().filter(|&&degree| degree == 2).count();
+
+        let from_start = Search::run(&graph, graph.node(self.start));
+        let far_end = from_start.furthest();
+        let longest = Search::run(&graph, far_end);
+        let longest_path = longest
+            .path_to(&graph, longest.furthest())
+            .unwrap_or_default();
+
+        let mut distances = vec![vec![None; self.width]; self.height];
+        for (node, &distance) in from_start.distances.iter().enumerate() {
+            let (x, y) = graph.cell(node);
+            distances[y as usize][x as usize] = distance;
+        }
+
+        MazeAnalysis {
+            dead_ends,
+            junctions: junctions.len(),
+            corridor_lengths,
+            branching_factor,
+            river_factor: corridor_cells as f64 / self.inside_count().max(1) as f64,
+            longest_path,
+            solution: from_start.path_to(&graph, graph.node(self.goal)),
+            distances,
+        }
+    }
+}
+
+// Histogram of the lengths of the runs of cells with two passages
+fn corridor_lengths(graph: &MazeGraph, degrees: &[usize]) -> Vec<usize> {
+    let mut lengths = Vec::new();
+    let mut seen = vec![false; graph.node_count()];
+    for node in 0..graph.node_count() {
+        if degrees[node] != 2 || seen[node] {
+            continue;
+        }
+        seen[node] = true;
+        let mut stack = vec![node];
+        let mut length = 0;
+        while let Some(current) = stack.pop() {
+            length += 1;
+            for &next in graph.neighbors(current) {
+                if degrees[next] == 2 && !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        if lengths.len() <= length {
+            lengths.resize(length + 1, 0);
+        }
+        lengths[length] += 1;
+    }
+    lengths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single row with every wall between neighbours removed
+    fn corridor(width: usize) -> Maze {
+        let mut maze = Maze::new(width, 1);
+        for x in 0..width as i32 - 1 {
+            maze.remove_wall(x, 0, x + 1, 0);
+        }
+        maze.start = (0, 0);
+        maze.goal = (width as i32 - 1, 0);
+        maze
+    }
+
+    #[test]
+    fn straight_corridor() {
+        let analysis = corridor(6).analyze();
+        assert_eq!(analysis.dead_ends, 2);
+        assert_eq!(analysis.junctions, 0);
+        assert_eq!(analysis.corridor_lengths, vec![0, 0, 0, 0, 1]);
+        assert_eq!(analysis.river_factor, 4.0 / 6.0);
+        assert_eq!(analysis.longest_path.len(), 6);
+        assert_eq!(analysis.solution.map(|path| path.len()), Some(6));
+        assert_eq!(analysis.distances[0][5], Some(5));
+    }
+
+    #[test]
+    fn longest_path_does_not_start_at_the_start() {
+        // A T with the start in the middle of its bar, five cells wide and three deep
+        let mut maze = Maze::new(5, 3);
+        for x in 0..4 {
+            maze.remove_wall(x, 0, x + 1, 0);
+        }
+        maze.remove_wall(2, 0, 2, 1);
+        maze.remove_wall(2, 1, 2, 2);
+        maze.start = (2, 0);
+        maze.goal = (2, 2);
+        let analysis = maze.analyze();
+        assert_eq!(analysis.dead_ends, 3);
+        assert_eq!(analysis.junctions, 1);
+        assert_eq!(analysis.branching_factor, 2.0);
+        assert_eq!(analysis.longest_path.len(), 5);
+        assert_eq!(analysis.solution.map(|path| path.len()), Some(3));
+        // Unreachable cells have no distance
+        assert_eq!(analysis.distances[2][0], None);
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_solution() {
+        let mut maze = corridor(4);
+        maze.add_wall(1, 0, 2, 0);
+        let analysis = maze.analyze();
+        assert!(analysis.solution.is_none());
+        assert_eq!(analysis.longest_path.len(), 2);
+    }
+}
@@ -0,0 +1,233 @@
+// Graph view of a maze: cells are nodes and open passages are edges.
+// Passability comes from `Maze::open_neighbors`, the same notion the solvers use,
+// so a maze that passes the connectivity checks can be solved by all of them.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::data::data_structures::{Direction, DisjointSet, Maze};
+
+/// Adjacency list of the open passages of a maze.
+/// Node `i` is the cell (i % width, i / width).
+pub struct MazeGraph {
+    pub width: usize,
+    pub adjacency: Vec<Vec<usize>>,
+}
+
+impl MazeGraph {
+    pub fn node(&self, (x, y): (i32, i32)) -> usize {
+        y as usize * self.width + x as usize
+    }
+
+    pub fn cell(&self, node: usize) -> (i32, i32) {
+        ((node % self.width) as i32, (node / self.width) as i32)
+    }
+
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.adjacency[node]
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+}
+
+/// How the cells of a maze are connected
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Connectivity {
+    /// Groups of cells reachable from each other
+    pub components: usize,
+    /// Passages that could be walled up without disconnecting anything,
+    /// 0 when the maze has no loops
+    pub loops: usize,
+}
+
+impl Connectivity {
+    pub fn has_loops(&self) -> bool {
+        self.loops > 0
+    }
+
+    /// Exactly one path between any two cells
+    pub fn is_perfect(&self) -> bool {
+        self.components == 1 && self.loops == 0
+    }
+}
+
+impl Maze {
+    pub fn to_graph(&self) -> MazeGraph {
+        let mut graph = MazeGraph {
+            width: self.width,
+            adjacency: Vec::with_capacity(self.width * self.height),
+        };
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let neighbors = self
+                    .open_neighbors(x, y)
+                    .into_iter()
+                    .map(|cell| graph.node(cell))
+                    .collect();
+                graph.adjacency.push(neighbors);
+            }
+        }
+        graph
+    }
+
+    pub fn connectivity(&self) -> Connectivity {
+        let graph = self.to_graph();
+        let mut sets = DisjointSet::new(graph.node_count());
+        let mut loops = 0;
+        for node in 0..graph.node_count() {
+            // Each passage once, from its lower node
+            for &other in graph.neighbors(node).iter().filter(|&&other| other > node) {
+                if !sets.union(node, other) {
+                    loops += 1;
+                }
+            }
+        }
+        // Masked cells are alone in their set but not a part of the maze
+        let masked = graph.node_count() - self.inside_count();
+        Connectivity {
+            components: sets.count() - masked,
+            loops,
+        }
+    }
+
+    /// Open one wall between each pair of parts of the maze that are not joined,
+    /// chosen at random among the walls between them
+    pub fn connect_regions<R: Rng>(&mut self, rng: &mut R) {
+        let graph = self.to_graph();
+        let mut sets = DisjointSet::new(graph.node_count());
+        let mut walls = Vec::new();
+        for node in 0..graph.node_count() {
+            for &other in graph.neighbors(node) {
+                sets.union(node, other);
+            }
+            let (x, y) = graph.cell(node);
+            for d in [Direction::East, Direction::South] {
+                if let Some(cell) = self.neighbor(x, y, d) {
+                    if self.has_wall(x, y, cell.0, cell.1) {
+                        walls.push(((x, y), cell));
+                    }
+                }
+            }
+        }
+        walls.shuffle(rng);
+        for ((x, y), (nx, ny)) in walls {
+            if sets.union(graph.node((x, y)), graph.node((nx, ny))) {
+                self.remove_wall(x, y, nx, ny);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::maze_generation::{generate_maze, MazeSettings, TerrainMode};
+    use crate::data::maze_mask::Mask;
+    use crate::menu::maze_menu::finite_generators;
+
+    #[test]
+    fn every_generator_builds_a_perfect_maze() {
+        for (name, create) in finite_generators() {
+            for seed in 0..5 {
+                let settings = MazeSettings {
+                    width: 12,
+                    height: 7,
+                    seed,
+                    terrain: TerrainMode::Uniform,
+                    braid: 0,
+                    sparseness: 0,
+                    mask: None,
+                };
+                let connectivity = generate_maze(name, create, settings).connectivity();
+                assert!(
+                    connectivity.is_perfect(),
+                    "{} with seed {}: {:?}",
+                    name,
+                    seed,
+                    connectivity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_generator_fills_a_mask() {
+        // A U shape, whose arms a row by row generator reaches separately
+        let u_shape = Mask::from_text("##   ##\n##   ##\n##   ##\n#######\n").unwrap();
+        let masks = [Mask::circle(13, 9), Mask::text("Hi", 8).unwrap(), u_shape];
+        for (name, create) in finite_generators() {
+            for (seed, mask) in masks.iter().enumerate() {
+                let settings = MazeSettings {
+                    width: 0,
+                    height: 0,
+                    seed: seed as u64,
+                    terrain: TerrainMode::Uniform,
+                    braid: 0,
+                    sparseness: 0,
+                    mask: Some(mask.clone()),
+                };
+                let maze = generate_maze(name, create, settings);
+                assert_eq!(maze.mask.as_ref(), Some(mask));
+                assert!(
+                    maze.connectivity().is_perfect(),
+                    "{} on mask {}",
+                    name,
+                    seed
+                );
+                assert!(maze.analyze().solution.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn closed_maze_has_one_component_per_cell() {
+        let connectivity = Maze::new(4, 3).connectivity();
+        assert_eq!(connectivity.components, 12);
+        assert!(!connectivity.has_loops());
+        assert!(!connectivity.is_perfect());
+    }
+
+    #[test]
+    fn open_maze_is_connected_with_loops() {
+        let maze = Maze::new_open(4, 3);
+        let connectivity = maze.connectivity();
+        assert_eq!(connectivity.components, 1);
+        // 17 passages, 11 of them in a spanning tree
+        assert_eq!(connectivity.loops, 17 - 11);
+    }
+
+    #[test]
+    fn carving_a_spanning_tree_gives_a_perfect_maze() {
+        // A comb: the top row open, every column open below it
+        let mut maze = Maze::new(5, 4);
+        for x in 0..4 {
+            maze.remove_wall(x, 0, x + 1, 0);
+        }
+        for x in 0..5 {
+            for y in 0..3 {
+                maze.remove_wall(x, y, x, y + 1);
+            }
+        }
+        assert!(maze.connectivity().is_perfect());
+
+        maze.remove_wall(0, 3, 1, 3);
+        let connectivity = maze.connectivity();
+        assert_eq!(connectivity.components, 1);
+        assert_eq!(connectivity.loops, 1);
+    }
+
+    #[test]
+    fn graph_follows_open_neighbors() {
+        let mut maze = Maze::new(3, 3);
+        maze.remove_wall(1, 1, 2, 1);
+        maze.remove_wall(1, 1, 1, 0);
+        let graph = maze.to_graph();
+        let center = graph.node((1, 1));
+        let mut nodes = graph.neighbors(center).to_vec();
+        nodes.sort();
+        assert_eq!(nodes, vec![graph.node((1, 0)), graph.node((2, 1))]);
+        assert_eq!(graph.neighbors(graph.node((2, 1))), &[center]);
+    }
+}
@@ -0,0 +1,668 @@
+// Saving and loading mazes, in two formats.
+// The ASCII format is the grid built by `Maze::wall_grid`, one line per row,
+// with `#` for walls, spaces for cells and open passages, and `S` and `G`
+// on the start and goal cells:
+//
+//     #######
+//     #S#   #
+//     # # # #
+//     #   #G#
+//     #######
+//
+// Masked cells are `#` on their cell position.
+//
+// The JSON format is a `MazeDocument`, which keeps everything about the maze:
+// walls as bitmasks, terrain, start and goal, the mask in the mask file format,
+// the generator and seed it was built with, and the last solver run on it.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::data_structures::{
+    Cell, Direction, Maze, Provenance, SolverRun, Terrain, GOAL_CHAR, PATH_CHAR, START_CHAR,
+    WALL_CHAR,
+};
+use crate::data::maze_mask::Mask;
+
+/// Why a maze could not be loaded. Lines and columns start at 1.
+#[derive(Debug)]
+pub enum MazeFileError {
+    Io(io::Error),
+    Empty,
+    // Rows must all have the same length
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    // The grid must be (2 * width + 1) x (2 * height + 1) with a size of at least 1x1
+    BadSize {
+        width: usize,
+        height: usize,
+    },
+    BadCharacter {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    // `S` or `G` placed on a wall position
+    MarkerOutsideCell {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    // The outer border, the wall corners and the walls of masked cells must be `#`
+    MissingWall {
+        line: usize,
+        column: usize,
+    },
+    DuplicateMarker {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    MissingStart,
+    MissingGoal,
+    // The ASCII format has room for one marker per cell
+    StartOnGoal,
+    // A mask without any cell inside
+    EmptyMask,
+    // A mask with a single cell, no room for both the start and the goal
+    TooFewCells,
+    Json(serde_json::Error),
+    // The JSON is well formed but does not describe a valid maze
+    InvalidDocument(String),
+}
+
+impl fmt::Display for MazeFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeFileError::Io(error) => write!(f, "{}", error),
+            MazeFileError::Empty => write!(f, "the file is empty"),
+            MazeFileError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} characters, expected {}",
+                line, found, expected
+            ),
+            MazeFileError::BadSize { width, height } => write!(
+                f,
+                "a {}x{} grid is not a maze, both sides must be odd and at least 3",
+                width, height
+            ),
+            MazeFileError::BadCharacter { line, column, ch } => write!(
+                f,
+                "unexpected character '{}' at line {}, column {}",
+                ch, line, column
+            ),
+            MazeFileError::MarkerOutsideCell { line, column, ch } => write!(
+                f,
+                "'{}' at line {}, column {} is not on a cell",
+                ch, line, column
+            ),
+            MazeFileError::MissingWall { line, column } => {
+                write!(f, "expected a wall at line {}, column {}", line, column)
+            }
+            MazeFileError::DuplicateMarker { line, column, ch } => {
+                write!(f, "second '{}' at line {}, column {}", ch, line, column)
+            }
+            MazeFileError::MissingStart => write!(f, "no start '{}' in the maze", START_CHAR),
+            MazeFileError::MissingGoal => write!(f, "no goal '{}' in the maze", GOAL_CHAR),
+            MazeFileError::StartOnGoal => {
+                write!(f, "the start and the goal are on the same cell")
+            }
+            MazeFileError::EmptyMask => write!(f, "the mask has no cell"),
+            MazeFileError::TooFewCells => {
+                write!(
+                    f,
+                    "the mask needs at least 2 cells for the start and the goal"
+                )
+            }
+            MazeFileError::Json(error) => write!(f, "invalid JSON: {}", error),
+            MazeFileError::InvalidDocument(reason) => write!(f, "invalid maze: {}", reason),
+        }
+    }
+}
+
+impl From<io::Error> for MazeFileError {
+    fn from(error: io::Error) -> MazeFileError {
+        MazeFileError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for MazeFileError {
+    fn from(error: serde_json::Error) -> MazeFileError {
+        MazeFileError::Json(error)
+    }
+}
+
+/// JSON form of a maze. Cell rows are listed from the top.
+#[derive(Serialize, Deserialize)]
+pub struct MazeDocument {
+    pub width: usize,
+    pub height: usize,
+    pub start: (i32, i32),
+    pub goal: (i32, i32),
+    // Walls of each cell, bit 0 for north, 1 for east, 2 for south and 3 for west
+    pub walls: Vec<Vec<u8>>,
+    pub terrain: Vec<Vec<Terrain>>,
+    pub origin: Option<Provenance>,
+    pub last_run: Option<SolverRun>,
+    // Rows of the mask, missing for a full rectangle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<Vec<String>>,
+}
+
+impl MazeDocument {
+    pub fn from_maze(maze: &Maze) -> MazeDocument {
+        MazeDocument {
+            width: maze.width,
+            height: maze.height,
+            start: maze.start,
+            goal: maze.goal,
+            walls: maze
+                .cells
+                .iter()
+                .map(|row| row.iter().map(wall_mask).collect())
+                .collect(),
+            terrain: maze
+                .cells
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.terrain).collect())
+                .collect(),
+            origin: maze.origin.clone(),
+            last_run: maze.last_run.clone(),
+            mask: maze.mask.as_ref().map(Mask::to_rows),
+        }
+    }
+
+    pub fn to_maze(&self) -> Result<Maze, MazeFileError> {
+        let invalid = |reason: String| Err(MazeFileError::InvalidDocument(reason));
+        if self.width == 0 || self.height == 0 {
+            return invalid(format!("{}x{} is not a maze size", self.width, self.height));
+        }
+        if self.walls.len() != self.height || self.walls.iter().any(|row| row.len() != self.width) {
+            return invalid(format!(
+                "walls are not a {}x{} grid",
+                self.width, self.height
+            ));
+        }
+        if self.terrain.len() != self.height
+            || self.terrain.iter().any(|row| row.len() != self.width)
+        {
+            return invalid(format!(
+                "terrain is not a {}x{} grid",
+                self.width, self.height
+            ));
+        }
+        let inside = |(x, y): (i32, i32)| {
+            x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32
+        };
+        if !inside(self.start) || !inside(self.goal) {
+            return invalid("start or goal outside of the maze".to_string());
+        }
+        if let Some(run) = &self.last_run {
+            if !run
+                .path
+                .iter()
+                .chain(&run.visit_order)
+                .all(|&cell| inside(cell))
+            {
+                return invalid("solver run goes outside of the maze".to_string());
+            }
+        }
+
+        let mut maze = Maze::new(self.width, self.height);
+        for (y, row) in self.walls.iter().enumerate() {
+            for (x, &mask) in row.iter().enumerate() {
+                if mask > 0b1111 {
+                    return invalid(format!("wall mask {} of cell ({}, {})", mask, x, y));
+                }
+                let cell = &mut maze.cells[y][x];
+                for d in Direction::ALL {
+                    cell.set_wall(d, mask & (1 << d.index()) != 0);
+                }
+                cell.terrain = self.terrain[y][x];
+            }
+        }
+        // Both sides of a wall must agree
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = &maze.cells[y][x];
+                let east = &maze.cells[y][(x + 1).min(self.width - 1)];
+                if x + 1 < self.width
+                    && cell.has_wall(Direction::East) != east.has_wall(Direction::West)
+                {
+                    return invalid(format!(
+                        "cells ({}, {}) and ({}, {}) disagree on their wall",
+                        x,
+                        y,
+                        x + 1,
+                        y
+                    ));
+                }
+                let south = &maze.cells[(y + 1).min(self.height - 1)][x];
+                if y + 1 < self.height
+                    && cell.has_wall(Direction::South) != south.has_wall(Direction::North)
+                {
+                    return invalid(format!(
+                        "cells ({}, {}) and ({}, {}) disagree on their wall",
+                        x,
+                        y,
+                        x,
+                        y + 1
+                    ));
+                }
+            }
+        }
+        // The outer border must be closed, solvers never look past it
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let cell = maze.get_cell(x, y);
+                let open_border = Direction::ALL.into_iter().any(|d| {
+                    let (dx, dy) = d.offset();
+                    let (nx, ny) = (x + dx, y + dy);
+                    let outside =
+                        nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32;
+                    outside && !cell.has_wall(d)
+                });
+                if open_border {
+                    return invalid(format!("cell ({}, {}) is open on the border", x, y));
+                }
+            }
+        }
+        if let Some(rows) = &self.mask {
+            let mask = Mask::from_rows(rows);
+            if mask.width != self.width || mask.height != self.height {
+                return invalid(format!("mask is not a {}x{} grid", self.width, self.height));
+            }
+            if mask.inside_count() < 2 {
+                return Err(MazeFileError::TooFewCells);
+            }
+            maze.set_mask(mask);
+        }
+        if !maze.is_inside(self.start.0, self.start.1) || !maze.is_inside(self.goal.0, self.goal.1)
+        {
+            return invalid("start or goal on a masked cell".to_string());
+        }
+        maze.start = self.start;
+        maze.goal = self.goal;
+        maze.origin = self.origin.clone();
+        maze.last_run = self.last_run.clone();
+        Ok(maze)
+    }
+}
+
+fn wall_mask(cell: &Cell) -> u8 {
+    Direction::ALL
+        .into_iter()
+        .filter(|&d| cell.has_wall(d))
+        .map(|d| 1 << d.index())
+        .sum()
+}
+
+impl Maze {
+    /// The maze in the ASCII format, with a trailing newline.
+    /// Fails when the start and the goal share a cell, which could not be loaded back.
+    pub fn to_ascii(&self) -> Result<String, MazeFileError> {
+        if self.start == self.goal {
+            return Err(MazeFileError::StartOnGoal);
+        }
+        let mut grid = self.wall_grid();
+        let (sx, sy) = self.start;
+        grid[(sy * 2 + 1) as usize][(sx * 2 + 1) as usize] = START_CHAR;
+        let (gx, gy) = self.goal;
+        grid[(gy * 2 + 1) as usize][(gx * 2 + 1) as usize] = GOAL_CHAR;
+
+        let mut text = String::new();
+        for row in grid {
+            text.extend(row);
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    /// Parse a maze in the ASCII format. Trailing empty lines and `\r` are ignored.
+    pub fn from_ascii(text: &str) -> Result<Maze, MazeFileError> {
+        let mut rows: Vec<Vec<char>> = text
+            .lines()
+            .map(|line| line.trim_end_matches('\r').chars().collect())
+            .collect();
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            return Err(MazeFileError::Empty);
+        }
+
+        let grid_width = rows[0].len();
+        for (y, row) in rows.iter().enumerate() {
+            if row.len() != grid_width {
+                return Err(MazeFileError::RaggedRow {
+                    line: y + 1,
+                    expected: grid_width,
+                    found: row.len(),
+                });
+            }
+        }
+        let grid_height = rows.len();
+        if grid_width < 3
+            || grid_height < 3
+            || grid_width.is_multiple_of(2)
+            || grid_height.is_multiple_of(2)
+        {
+            return Err(MazeFileError::BadSize {
+                width: grid_width,
+                height: grid_height,
+            });
+        }
+
+        let mut maze = Maze::new(grid_width / 2, grid_height / 2);
+        let mut masked = vec![vec![false; maze.width]; maze.height];
+        let mut start = None;
+        let mut goal = None;
+        for (gy, row) in rows.iter().enumerate() {
+            for (gx, &ch) in row.iter().enumerate() {
+                let (line, column) = (gy + 1, gx + 1);
+                let on_border = gx == 0 || gy == 0 || gx == grid_width - 1 || gy == grid_height - 1;
+                let is_cell = gx % 2 == 1 && gy % 2 == 1;
+                // Corners between four cells can never be open
+                let is_corner = gx % 2 == 0 && gy % 2 == 0;
+
+                match ch {
+                    // A wall on a cell position masks the cell
+                    WALL_CHAR if is_cell => masked[gy / 2][gx / 2] = true,
+                    WALL_CHAR => {}
+                    PATH_CHAR | START_CHAR | GOAL_CHAR if on_border || is_corner => {
+                        return Err(MazeFileError::MissingWall { line, column });
+                    }
+                    START_CHAR | GOAL_CHAR if !is_cell => {
+                        return Err(MazeFileError::MarkerOutsideCell { line, column, ch });
+                    }
+                    START_CHAR | GOAL_CHAR => {
+                        let marker = if ch == START_CHAR {
+                            &mut start
+                        } else {
+                            &mut goal
+                        };
+                        if marker.is_some() {
+                            return Err(MazeFileError::DuplicateMarker { line, column, ch });
+                        }
+                        *marker = Some(((gx / 2) as i32, (gy / 2) as i32));
+                    }
+                    PATH_CHAR if is_cell => {}
+                    // Masked cells are closed on every side
+                    PATH_CHAR
+                        if rows[gy | 1][gx | 1] == WALL_CHAR
+                            || rows[(gy - 1) | 1][(gx - 1) | 1] == WALL_CHAR =>
+                    {
+                        return Err(MazeFileError::MissingWall { line, column });
+                    }
+                    PATH_CHAR => {
+                        // An open passage between the two cells on either side
+                        let (x, y) = ((gx - 1) / 2, (gy - 1) / 2);
+                        if gx % 2 == 0 {
+                            maze.remove_wall(x as i32, y as i32, x as i32 + 1, y as i32);
+                        } else {
+                            maze.remove_wall(x as i32, y as i32, x as i32, y as i32 + 1);
+                        }
+                    }
+                    _ => return Err(MazeFileError::BadCharacter { line, column, ch }),
+                }
+            }
+        }
+
+        if masked.iter().flatten().any(|&masked| masked) {
+            let cells = masked
+                .iter()
+                .map(|row| row.iter().map(|&masked| !masked).collect())
+                .collect();
+            let mask = Mask::from_cells(cells);
+            if mask.inside_count() < 2 {
+                return Err(MazeFileError::TooFewCells);
+            }
+            maze.set_mask(mask);
+        }
+        maze.start = start.ok_or(MazeFileError::MissingStart)?;
+        maze.goal = goal.ok_or(MazeFileError::MissingGoal)?;
+        Ok(maze)
+    }
+
+    pub fn save_ascii(&self, path: &Path) -> Result<(), MazeFileError> {
+        fs::write(path, self.to_ascii()?)?;
+        Ok(())
+    }
+
+    pub fn load_ascii(path: &Path) -> Result<Maze, MazeFileError> {
+        Maze::from_ascii(&fs::read_to_string(path)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        // A document only holds plain data, serializing it cannot fail
+        serde_json::to_string_pretty(&MazeDocument::from_maze(self)).unwrap()
+    }
+
+    pub fn from_json(text: &str) -> Result<Maze, MazeFileError> {
+        let document: MazeDocument = serde_json::from_str(text)?;
+        document.to_maze()
+    }
+
+    pub fn save_json(&self, path: &Path) -> Result<(), MazeFileError> {
+        fs::write(path, self.to_json())?;
+        Ok(())
+    }
+
+    pub fn load_json(path: &Path) -> Result<Maze, MazeFileError> {
+        Maze::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::maze_generation::{
+        create, generate_maze, MazeSettings, Prim, TerrainMode,
+    };
+
+    const SMALL: &str = "\
+#######
+#S#   #
+# # # #
+#   #G#
+#######
+";
+
+    // Error of `from_ascii` for `text`, which must not parse
+    fn ascii_error(text: &str) -> MazeFileError {
+        Maze::from_ascii(text)
+            .err()
+            .expect("the maze should not parse")
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let maze = Maze::from_ascii(SMALL).unwrap();
+        assert_eq!((maze.width, maze.height), (3, 2));
+        assert_eq!((maze.start, maze.goal), ((0, 0), (2, 1)));
+        assert!(maze.has_wall(0, 0, 1, 0));
+        assert!(!maze.has_wall(0, 0, 0, 1));
+        assert_eq!(maze.to_ascii().unwrap(), SMALL);
+        // Windows line endings and trailing empty lines are accepted
+        let crlf = SMALL.replace('\n', "\r\n") + "\r\n";
+        assert_eq!(Maze::from_ascii(&crlf).unwrap().to_ascii().unwrap(), SMALL);
+    }
+
+    #[test]
+    fn empty_file() {
+        assert!(matches!(ascii_error("\n\n"), MazeFileError::Empty));
+    }
+
+    #[test]
+    fn ragged_row() {
+        let text = SMALL.replace("# # # #", "# # #");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::RaggedRow {
+                line: 3,
+                expected: 7,
+                found: 5
+            }
+        ));
+    }
+
+    #[test]
+    fn bad_size() {
+        assert!(matches!(
+            ascii_error("####\n#SG#\n####\n"),
+            MazeFileError::BadSize {
+                width: 4,
+                height: 3
+            }
+        ));
+        assert!(matches!(
+            ascii_error("#\n"),
+            MazeFileError::BadSize {
+                width: 1,
+                height: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn bad_character() {
+        let text = SMALL.replace("#   #G#", "# x #G#");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::BadCharacter {
+                line: 4,
+                column: 3,
+                ch: 'x'
+            }
+        ));
+    }
+
+    #[test]
+    fn marker_outside_cell() {
+        let text = SMALL.replace("#S#", "# #").replace("#   #G#", "# S #G#");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::MarkerOutsideCell {
+                line: 4,
+                column: 3,
+                ch: 'S'
+            }
+        ));
+    }
+
+    #[test]
+    fn missing_wall() {
+        // On the border
+        let text = SMALL.replace("#S#   #", " S#   #");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::MissingWall { line: 2, column: 1 }
+        ));
+        // On a corner between four cells
+        let text = SMALL.replace("# # # #", "#   # #");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::MissingWall { line: 3, column: 3 }
+        ));
+        // Next to a masked cell
+        let text = SMALL.replace("#S#   #", "#S#  ##");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::MissingWall { line: 2, column: 5 }
+        ));
+    }
+
+    #[test]
+    fn duplicate_marker() {
+        let text = SMALL.replace("#S#   #", "#S#S  #");
+        assert!(matches!(
+            ascii_error(&text),
+            MazeFileError::DuplicateMarker {
+                line: 2,
+                column: 4,
+                ch: 'S'
+            }
+        ));
+    }
+
+    #[test]
+    fn missing_markers() {
+        let text = SMALL.replace('S', " ");
+        assert!(matches!(ascii_error(&text), MazeFileError::MissingStart));
+        let text = SMALL.replace('G', " ");
+        assert!(matches!(ascii_error(&text), MazeFileError::MissingGoal));
+    }
+
+    #[test]
+    fn start_on_goal_is_not_saved() {
+        let mut maze = Maze::from_ascii(SMALL).unwrap();
+        maze.goal = maze.start;
+        assert!(matches!(maze.to_ascii(), Err(MazeFileError::StartOnGoal)));
+    }
+
+    #[test]
+    fn missing_file() {
+        let error = Maze::load_ascii(Path::new("no/such/maze.txt")).err();
+        assert!(matches!(error, Some(MazeFileError::Io(_))));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let settings = MazeSettings {
+            width: 9,
+            height: 6,
+            seed: 11,
+            terrain: TerrainMode::Random,
+            braid: 20,
+            sparseness: 0,
+            mask: None,
+        };
+        let mut maze = generate_maze("Prim's Algorithm", create::<Prim>, settings);
+        let path = maze.analyze().solution.unwrap();
+        maze.last_run = Some(SolverRun {
+            solver: "BFS".to_string(),
+            path_cost: path.len() as i32 - 1,
+            visit_order: path.iter().rev().copied().collect(),
+            path,
+        });
+
+        let json = maze.to_json();
+        let loaded = Maze::from_json(&json).unwrap();
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(loaded.wall_grid(), maze.wall_grid());
+        assert_eq!(loaded.origin, maze.origin);
+        assert_eq!(loaded.last_run, maze.last_run);
+        let terrain = |maze: &Maze| -> Vec<Terrain> {
+            maze.cells
+                .iter()
+                .flatten()
+                .map(|cell| cell.terrain)
+                .collect()
+        };
+        assert_eq!(terrain(&loaded), terrain(&maze));
+    }
+
+    #[test]
+    fn json_with_an_open_border() {
+        let maze = Maze::from_ascii(SMALL).unwrap();
+        let mut document = MazeDocument::from_maze(&maze);
+        // Open the west wall of the bottom left cell
+        document.walls[1][0] &= !(1 << Direction::West.index());
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(matches!(
+            Maze::from_json(&json),
+            Err(MazeFileError::InvalidDocument(_))
+        ));
+    }
+}
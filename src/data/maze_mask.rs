@@ -0,0 +1,627 @@
+// Masks give mazes a shape other than a rectangle. Masked cells are not part of
+// the maze: their walls always stay up, `Maze::neighbor` never returns them and
+// the generators and solvers only ever work on the cells inside.
+//
+// Mask files are plain text, one line per row of cells. A space, or the end of
+// a line shorter than the others, is outside the maze and any other character
+// is a cell, so a shape can be drawn with any character:
+//
+//     ..    ..
+//     ...  ...
+//     ........
+//     .. .. ..
+//     ..    ..
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use crate::data::data_structures::{Direction, Maze};
+use crate::data::maze_io::MazeFileError;
+
+// Size of the glyphs of `FONT` in font pixels
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+// Character written for the cells when a mask is turned back into text
+const INSIDE_CHAR: char = '.';
+const OUTSIDE_CHAR: char = ' ';
+
+// 5x7 glyphs for the text masks, '#' for the pixels that are set
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 37] = [
+    (
+        ' ',
+        [
+            ".....", ".....", ".....", ".....", ".....", ".....", ".....",
+        ],
+    ),
+    (
+        'A',
+        [
+            ".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#",
+        ],
+    ),
+    (
+        'B',
+        [
+            "####.", "#...#", "#...#", "####.", "#...#", "#...#", "####.",
+        ],
+    ),
+    (
+        'C',
+        [
+            ".###.", "#...#", "#....", "#....", "#....", "#...#", ".###.",
+        ],
+    ),
+    (
+        'D',
+        [
+            "####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####.",
+        ],
+    ),
+    (
+        'E',
+        [
+            "#####", "#....", "#....", "####.", "#....", "#....", "#####",
+        ],
+    ),
+    (
+        'F',
+        [
+            "#####", "#....", "#....", "####.", "#....", "#....", "#....",
+        ],
+    ),
+    (
+        'G',
+        [
+            ".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        'H',
+        [
+            "#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#",
+        ],
+    ),
+    (
+        'I',
+        [
+            ".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###.",
+        ],
+    ),
+    (
+        'J',
+        [
+            "..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##..",
+        ],
+    ),
+    (
+        'K',
+        [
+            "#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#",
+        ],
+    ),
+    (
+        'L',
+        [
+            "#....", "#....", "#....", "#....", "#....", "#....", "#####",
+        ],
+    ),
+    (
+        'M',
+        [
+            "#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#",
+        ],
+    ),
+    (
+        'N',
+        [
+            "#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#",
+        ],
+    ),
+    (
+        'O',
+        [
+            ".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        'P',
+        [
+            "####.", "#...#", "#...#", "####.", "#....", "#....", "#....",
+        ],
+    ),
+    (
+        'Q',
+        [
+            ".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#",
+        ],
+    ),
+    (
+        'R',
+        [
+            "####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#",
+        ],
+    ),
+    (
+        'S',
+        [
+            ".####", "#....", "#....", ".###.", "....#", "....#", "####.",
+        ],
+    ),
+    (
+        'T',
+        [
+            "#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#..",
+        ],
+    ),
+    (
+        'U',
+        [
+            "#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        'V',
+        [
+            "#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#..",
+        ],
+    ),
+    (
+        'W',
+        [
+            "#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#.",
+        ],
+    ),
+    (
+        'X',
+        [
+            "#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#",
+        ],
+    ),
+    (
+        'Y',
+        [
+            "#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#..",
+        ],
+    ),
+    (
+        'Z',
+        [
+            "#####", "....#", "...#.", "..#..", ".#...", "#....", "#####",
+        ],
+    ),
+    (
+        '0',
+        [
+            ".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '1',
+        [
+            "..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###.",
+        ],
+    ),
+    (
+        '2',
+        [
+            ".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####",
+        ],
+    ),
+    (
+        '3',
+        [
+            "#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '4',
+        [
+            "...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#.",
+        ],
+    ),
+    (
+        '5',
+        [
+            "#####", "#....", "####.", "....#", "....#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '6',
+        [
+            "..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '7',
+        [
+            "#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#...",
+        ],
+    ),
+    (
+        '8',
+        [
+            ".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###.",
+        ],
+    ),
+    (
+        '9',
+        [
+            ".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##..",
+        ],
+    ),
+];
+
+/// Cells of a maze that are part of its shape
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mask {
+    pub width: usize,
+    pub height: usize,
+    // True for the cells inside, indexed by [y][x]
+    cells: Vec<Vec<bool>>,
+}
+
+impl Mask {
+    /// Mask of exactly these cells, all rows must have the same length
+    pub fn from_cells(cells: Vec<Vec<bool>>) -> Mask {
+        Mask {
+            width: cells.first().map_or(0, Vec::len),
+            height: cells.len(),
+            cells,
+        }
+    }
+
+    /// Parse a mask file. Only the largest connected part of the shape is kept,
+    /// cropped to its bounding box.
+    pub fn from_text(text: &str) -> Result<Mask, MazeFileError> {
+        let cells = text_cells(text);
+        if cells.is_empty() {
+            return Err(MazeFileError::Empty);
+        }
+        shape(cells)
+    }
+
+    pub fn load(path: &Path) -> Result<Mask, MazeFileError> {
+        Mask::from_text(&fs::read_to_string(path)?)
+    }
+
+    /// Mask of exactly the cells of `rows`, in the mask file format.
+    /// Unlike `from_text` nothing is cropped or dropped.
+    pub fn from_rows(rows: &[String]) -> Mask {
+        Mask::from_cells(text_cells(&rows.join("\n")))
+    }
+
+    /// Rows in the mask file format, padded to the width of the mask
+    pub fn to_rows(&self) -> Vec<String> {
+        self.cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&inside| if inside { INSIDE_CHAR } else { OUTSIDE_CHAR })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Circle filling a `width` x `height` rectangle, stretched to an ellipse
+    /// when the sides differ
+    pub fn circle(width: usize, height: usize) -> Mask {
+        let (rx, ry) = (width as f64 / 2.0, height as f64 / 2.0);
+        let cells = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let dx = (x as f64 + 0.5 - rx) / rx;
+                        let dy = (y as f64 + 0.5 - ry) / ry;
+                        dx * dx + dy * dy <= 1.0
+                    })
+                    .collect()
+            })
+            .collect();
+        Mask::from_cells(cells)
+    }
+
+    /// `text` written with the built-in font, letters and digits only, scaled
+    /// so that it is about `height` cells tall. The letters stand on an
+    /// underline that joins them into a single shape.
+    pub fn text(text: &str, height: usize) -> Result<Mask, MazeFileError> {
+        let glyphs = text
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| {
+                FONT.iter()
+                    .find(|(glyph, _)| *glyph == ch.to_ascii_uppercase())
+                    .map(|(_, rows)| rows)
+                    .ok_or(MazeFileError::BadCharacter {
+                        line: 1,
+                        column: i + 1,
+                        ch,
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if glyphs.is_empty() {
+            return Err(MazeFileError::EmptyMask);
+        }
+
+        // Font pixels, with a column between the letters and the underline below
+        let width = glyphs.len() * (GLYPH_WIDTH + 1) - 1;
+        let mut pixels = vec![vec![false; width]; GLYPH_HEIGHT + 1];
+        for (i, rows) in glyphs.iter().enumerate() {
+            for (y, row) in rows.iter().enumerate() {
+                for (x, ch) in row.chars().enumerate() {
+                    pixels[y][i * (GLYPH_WIDTH + 1) + x] = ch == '#';
+                }
+            }
+        }
+        pixels[GLYPH_HEIGHT] = vec![true; width];
+        thicken_diagonals(&mut pixels);
+
+        let scale = (height / (GLYPH_HEIGHT + 1)).max(1);
+        let cells = pixels
+            .iter()
+            .flat_map(|row| {
+                let scaled: Vec<bool> = row
+                    .iter()
+                    .flat_map(|&pixel| std::iter::repeat_n(pixel, scale))
+                    .collect();
+                std::iter::repeat_n(scaled, scale)
+            })
+            .collect();
+        shape(cells)
+    }
+
+    pub fn is_inside(&self, x: usize, y: usize) -> bool {
+        self.cells[y][x]
+    }
+
+    pub fn inside_count(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|&&inside| inside)
+            .count()
+    }
+
+    /// Append a row of cells that are all inside, for mazes growing downward
+    pub fn push_row(&mut self) {
+        self.cells.push(vec![true; self.width]);
+        self.height += 1;
+    }
+}
+
+// Cells of a mask file, padded to the longest line
+fn text_cells(text: &str) -> Vec<Vec<bool>> {
+    let lines: Vec<&str> = text.lines().map(|line| line.trim_end()).collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut cells: Vec<Vec<bool>> = lines
+        .iter()
+        .map(|line| {
+            let mut row: Vec<bool> = line.chars().map(|ch| ch != OUTSIDE_CHAR).collect();
+            row.resize(width, false);
+            row
+        })
+        .collect();
+    while cells.last().is_some_and(|row| !row.contains(&true)) {
+        cells.pop();
+    }
+    cells
+}
+
+// Cells only touching at a corner are not connected in a maze, join them
+// by setting one of the two other pixels of their square
+fn thicken_diagonals(pixels: &mut [Vec<bool>]) {
+    for y in 0..pixels.len() - 1 {
+        for x in 0..pixels[y].len() - 1 {
+            let (a, b) = (pixels[y][x], pixels[y][x + 1]);
+            let (c, d) = (pixels[y + 1][x], pixels[y + 1][x + 1]);
+            if (a && d && !b && !c) || (b && c && !a && !d) {
+                pixels[y + 1][x] = true;
+                pixels[y + 1][x + 1] = true;
+            }
+        }
+    }
+}
+
+// Keep the largest connected part of `cells` and crop it to its bounding box,
+// so that every cell of the mask can be reached. That part needs two cells,
+// one for the start and one for the goal
+fn shape(cells: Vec<Vec<bool>>) -> Result<Mask, MazeFileError> {
+    let mask = Mask::from_cells(cells);
+    let mut part = vec![vec![None; mask.width]; mask.height];
+    let mut largest: Option<(usize, usize)> = None;
+    let mut parts = 0;
+    for y in 0..mask.height {
+        for x in 0..mask.width {
+            if !mask.cells[y][x] || part[y][x].is_some() {
+                continue;
+            }
+            part[y][x] = Some(parts);
+            let mut size = 0;
+            let mut queue = VecDeque::from([(x, y)]);
+            while let Some((cx, cy)) = queue.pop_front() {
+                size += 1;
+                for d in Direction::ALL {
+                    let (dx, dy) = d.offset();
+                    let (nx, ny) = (cx as i32 + dx, cy as i32 + dy);
+                    if nx < 0 || ny < 0 || nx >= mask.width as i32 || ny >= mask.height as i32 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    if mask.cells[ny][nx] && part[ny][nx].is_none() {
+                        part[ny][nx] = Some(parts);
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            if largest.is_none_or(|(_, best)| size > best) {
+                largest = Some((parts, size));
+            }
+            parts += 1;
+        }
+    }
+    let Some((kept, size)) = largest else {
+        return Err(MazeFileError::EmptyMask);
+    };
+    if size < 2 {
+        return Err(MazeFileError::TooFewCells);
+    }
+
+    let inside = |x: usize, y: usize| part[y][x] == Some(kept);
+    let rows: Vec<usize> = (0..mask.height)
+        .filter(|&y| (0..mask.width).any(|x| inside(x, y)))
+        .collect();
+    let columns: Vec<usize> = (0..mask.width)
+        .filter(|&x| (0..mask.height).any(|y| inside(x, y)))
+        .collect();
+    let (x0, x1) = (columns[0], columns[columns.len() - 1]);
+    let (y0, y1) = (rows[0], rows[rows.len() - 1]);
+    let cells = (y0..=y1)
+        .map(|y| (x0..=x1).map(|x| inside(x, y)).collect())
+        .collect();
+    Ok(Mask::from_cells(cells))
+}
+
+impl Maze {
+    /// Closed maze in the shape of `mask`
+    pub fn with_mask(mask: Mask) -> Maze {
+        let mut maze = Maze::new(mask.width, mask.height);
+        maze.set_mask(mask);
+        maze
+    }
+
+    /// Mask cells of the maze, which must have the size of the mask.
+    /// Walls around the masked cells are put back up, and the start and goal
+    /// move to the first and last cells inside if they were masked.
+    pub fn set_mask(&mut self, mask: Mask) {
+        assert!(mask.width == self.width && mask.height == self.height);
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if mask.is_inside(x as usize, y as usize) {
+                    continue;
+                }
+                for d in Direction::ALL {
+                    self.get_cell_mut(x, y).set_wall(d, true);
+                    let (dx, dy) = d.offset();
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx >= 0 && ny >= 0 && nx < self.width as i32 && ny < self.height as i32 {
+                        self.get_cell_mut(nx, ny).set_wall(d.opposite(), true);
+                    }
+                }
+            }
+        }
+        self.mask = Some(mask);
+
+        let cells = self.inside_cells();
+        if !self.is_inside(self.start.0, self.start.1) {
+            self.start = cells.first().copied().unwrap_or((0, 0));
+        }
+        if !self.is_inside(self.goal.0, self.goal.1) {
+            self.goal = cells.last().copied().unwrap_or((0, 0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_file_keeps_the_largest_part() {
+        let mask = Mask::from_text("\n  ##  #\n  ##\n  #\n\n").unwrap();
+        assert_eq!(mask.width, 2);
+        assert_eq!(mask.height, 3);
+        assert_eq!(mask.inside_count(), 5);
+        assert_eq!(mask.to_rows(), vec!["..", "..", ". "]);
+        assert!(matches!(
+            Mask::from_text("   \n"),
+            Err(MazeFileError::Empty)
+        ));
+    }
+
+    #[test]
+    fn rows_round_trip() {
+        let mask = Mask::circle(9, 6);
+        assert_eq!(Mask::from_rows(&mask.to_rows()), mask);
+    }
+
+    #[test]
+    fn circle_is_symmetric() {
+        let mask = Mask::circle(10, 10);
+        for y in 0..10 {
+            for x in 0..10 {
+                assert_eq!(mask.is_inside(x, y), mask.is_inside(9 - x, y));
+                assert_eq!(mask.is_inside(x, y), mask.is_inside(x, 9 - y));
+            }
+        }
+        assert!(mask.is_inside(5, 5));
+        assert!(!mask.is_inside(0, 0));
+    }
+
+    #[test]
+    fn text_is_a_single_shape() {
+        for text in ["HELLO", "xyz", "A1 B2", "WQ07"] {
+            let mask = Mask::text(text, 16).unwrap();
+            // Nothing dropped: the underline alone spans the whole text
+            let expected = text.len() * 2 * (GLYPH_WIDTH + 1) - 2;
+            assert_eq!(mask.width, expected, "{}", text);
+            assert_eq!(mask.height, 2 * (GLYPH_HEIGHT + 1));
+        }
+        assert!(matches!(
+            Mask::text("a?", 8),
+            Err(MazeFileError::BadCharacter { column: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn masked_cells_are_never_opened() {
+        let mut maze = Maze::with_mask(Mask::from_text("###\n# #\n###").unwrap());
+        assert!(!maze.is_inside(1, 1));
+        maze.remove_wall(0, 1, 1, 1);
+        maze.open_all();
+        assert!(maze.get_cell(1, 1).walls.iter().all(|&wall| wall));
+        assert!(maze.get_cell(0, 1).has_wall(Direction::East));
+        assert_eq!(maze.get_neighbors(1, 0), vec![(0, 0), (2, 0)]);
+        assert_eq!(maze.inside_count(), 8);
+        // The masked cell is not a part of its own, the open ring is one loop
+        let connectivity = maze.connectivity();
+        assert_eq!(connectivity.components, 1);
+        assert_eq!(connectivity.loops, 1);
+    }
+
+    #[test]
+    fn masked_maze_survives_saving() {
+        let mut maze = Maze::with_mask(Mask::circle(7, 5));
+        maze.open_all();
+        for loaded in [
+            Maze::from_ascii(&maze.to_ascii().unwrap()).unwrap(),
+            Maze::from_json(&maze.to_json()).unwrap(),
+        ] {
+            assert_eq!(loaded.mask, maze.mask);
+            assert_eq!(loaded.wall_grid(), maze.wall_grid());
+            assert_eq!((loaded.start, loaded.goal), (maze.start, maze.goal));
+        }
+    }
+
+    #[test]
+    fn single_cell_is_not_a_maze() {
+        // The largest part has one cell, whatever the other parts
+        for text in ["#", "# #\n\n #"] {
+            assert!(matches!(
+                Mask::from_text(text),
+                Err(MazeFileError::TooFewCells)
+            ));
+        }
+        let mut maze = Maze::new(2, 1);
+        (maze.start, maze.goal) = ((0, 0), (1, 0));
+        maze.mask = Some(Mask::from_cells(vec![vec![false, true]]));
+        assert!(matches!(
+            Maze::from_json(&maze.to_json()),
+            Err(MazeFileError::TooFewCells)
+        ));
+    }
+}
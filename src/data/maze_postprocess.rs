@@ -0,0 +1,153 @@
+// Passes run over a finished maze to change its texture.
+// Braiding knocks dead ends through into their neighbours, creating loops so
+// that there are several routes to the goal. Sparsifying fills dead ends in,
+// walling cells off until only the core of the maze is left.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::data::data_structures::Maze;
+
+impl Maze {
+    /// Cells with a single passage
+    pub fn dead_ends(&self) -> Vec<(i32, i32)> {
+        let mut dead_ends = Vec::new();
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                if self.open_neighbors(x, y).len() == 1 {
+                    dead_ends.push((x, y));
+                }
+            }
+        }
+        dead_ends
+    }
+
+    /// Remove `percent` of the dead ends by opening a wall of each.
+    /// A dead end is joined to another dead end when it has one next to it, so
+    /// that a single wall gets rid of both. Walled off cells are never joined.
+    pub fn braid<R: Rng>(&mut self, percent: u8, rng: &mut R) {
+        let mut dead_ends = self.dead_ends();
+        dead_ends.shuffle(rng);
+        let count = (dead_ends.len() * percent.min(100) as usize).div_ceil(100);
+        for &(x, y) in dead_ends.iter().take(count) {
+            // Already joined by an earlier dead end
+            if self.open_neighbors(x, y).len() != 1 {
+                continue;
+            }
+            let closed: Vec<(i32, i32)> = self
+                .get_neighbors(x, y)
+                .into_iter()
+                .filter(|&(nx, ny)| {
+                    self.has_wall(x, y, nx, ny) && !self.open_neighbors(nx, ny).is_empty()
+                })
+                .collect();
+            let joining: Vec<(i32, i32)> = closed
+                .iter()
+                .copied()
+                .filter(|&(nx, ny)| self.open_neighbors(nx, ny).len() == 1)
+                .collect();
+            let candidates = if joining.is_empty() { closed } else { joining };
+            if let Some(&(nx, ny)) = candidates.choose(rng) {
+                self.remove_wall(x, y, nx, ny);
+            }
+        }
+    }
+
+    /// Wall off dead ends one at a time until `percent` of the cells are walled off,
+    /// or no dead end is left. The start and the goal are never filled, so they
+    /// stay connected.
+    pub fn sparsify<R: Rng>(&mut self, percent: u8, rng: &mut R) {
+        let target = self.inside_count() * percent.min(100) as usize / 100;
+        let keep = [self.start, self.goal];
+        let mut dead_ends: Vec<(i32, i32)> = self
+            .dead_ends()
+            .into_iter()
+            .filter(|cell| !keep.contains(cell))
+            .collect();
+        let mut filled = 0;
+        while filled < target && !dead_ends.is_empty() {
+            let (x, y) = dead_ends.swap_remove(rng.gen_range(0..dead_ends.len()));
+            let open = self.open_neighbors(x, y);
+            if open.len() != 1 {
+                continue;
+            }
+            let (nx, ny) = open[0];
+            self.add_wall(x, y, nx, ny);
+            filled += 1;
+            // Filling may leave the neighbour as a new dead end
+            if !keep.contains(&(nx, ny)) && self.open_neighbors(nx, ny).len() == 1 {
+                dead_ends.push((nx, ny));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::maze_generation::{
+        create, generate_maze, MazeSettings, RecursiveBacktracker, TerrainMode,
+    };
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn backtracker(seed: u64) -> Maze {
+        let settings = MazeSettings {
+            width: 16,
+            height: 10,
+            seed,
+            terrain: TerrainMode::Uniform,
+            braid: 0,
+            sparseness: 0,
+            mask: None,
+        };
+        generate_maze(
+            "Recursive Backtracker",
+            create::<RecursiveBacktracker>,
+            settings,
+        )
+    }
+
+    #[test]
+    fn full_braid_leaves_no_dead_end() {
+        for seed in 0..5 {
+            let mut maze = backtracker(seed);
+            maze.braid(100, &mut StdRng::seed_from_u64(seed));
+            assert!(maze.dead_ends().is_empty());
+            let connectivity = maze.connectivity();
+            assert_eq!(connectivity.components, 1);
+            assert!(connectivity.has_loops());
+        }
+    }
+
+    #[test]
+    fn partial_braid_removes_some_dead_ends() {
+        let mut maze = backtracker(7);
+        let before = maze.dead_ends().len();
+        maze.braid(50, &mut StdRng::seed_from_u64(7));
+        let after = maze.dead_ends().len();
+        assert!(after < before);
+        assert!(after > 0);
+    }
+
+    #[test]
+    fn sparsify_keeps_start_and_goal_connected() {
+        for seed in 0..5 {
+            let mut maze = backtracker(seed);
+            maze.sparsify(40, &mut StdRng::seed_from_u64(seed));
+            let analysis = maze.analyze();
+            assert!(analysis.solution.is_some());
+            let walled_off = analysis.distances.iter().flatten().filter(|d| d.is_none());
+            assert_eq!(walled_off.count(), 16 * 10 * 40 / 100);
+        }
+    }
+
+    #[test]
+    fn full_sparsify_leaves_only_the_solution() {
+        let mut maze = backtracker(3);
+        maze.sparsify(100, &mut StdRng::seed_from_u64(3));
+        let analysis = maze.analyze();
+        let reachable = analysis.distances.iter().flatten().flatten().count();
+        assert_eq!(Some(reachable), analysis.solution.map(|path| path.len()));
+    }
+}
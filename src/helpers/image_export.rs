@@ -0,0 +1,291 @@
+// Export of mazes to images: PPM and PNG rasters, and SVG.
+// The maze is laid out like `Maze::wall_grid`, with wall rows and columns
+// `wall_thickness` pixels wide and cell rows and columns `cell_size` pixels wide.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use console_engine::Color;
+
+use crate::data::data_structures::{Maze, Terrain, WALL_CHAR};
+use crate::menu::maze_scene::MazeScene;
+
+// Largest raster image, about 75 MB of RGB pixels
+pub const MAX_PIXELS: usize = 25_000_000;
+
+/// Sizes in pixels and colors of an exported image
+pub struct ImageStyle {
+    pub cell_size: usize,
+    pub wall_thickness: usize,
+    pub color_wall: Color,
+    pub color_path: Color,
+    pub color_start: Color,
+    pub color_goal: Color,
+    pub color_solution: Color,
+    pub color_grass: Color,
+    pub color_mud: Color,
+    pub color_water: Color,
+}
+
+impl ImageStyle {
+    /// Style using the colors of `scene`, so images look like the terminal
+    pub fn from_scene(scene: &MazeScene, cell_size: usize, wall_thickness: usize) -> ImageStyle {
+        ImageStyle {
+            cell_size: cell_size.max(1),
+            wall_thickness: wall_thickness.max(1),
+            color_wall: scene.color_wall,
+            color_path: scene.color_path,
+            color_start: scene.color_start,
+            color_goal: scene.color_goal,
+            color_solution: scene.color_solution,
+            color_grass: scene.color_grass,
+            color_mud: scene.color_mud,
+            color_water: scene.color_water,
+        }
+    }
+
+    // Pixel size of grid row or column `i`, walls sit on even indexes
+    fn span(&self, i: usize) -> usize {
+        if i.is_multiple_of(2) {
+            self.wall_thickness
+        } else {
+            self.cell_size
+        }
+    }
+
+    // Pixel offset of grid row or column `i`
+    fn offset(&self, i: usize) -> usize {
+        (i / 2) * (self.wall_thickness + self.cell_size) + (i % 2) * self.wall_thickness
+    }
+}
+
+/// Color of every element of the wall grid. With `with_path`, the path of the
+/// last solver run is drawn over the maze.
+fn color_grid(maze: &Maze, style: &ImageStyle, with_path: bool) -> Vec<Vec<Color>> {
+    let mut grid: Vec<Vec<Color>> = maze
+        .wall_grid()
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&ch| {
+                    if ch == WALL_CHAR {
+                        style.color_wall
+                    } else {
+                        style.color_path
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    for (y, row) in maze.cells.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            // Masked cells stay filled in like walls
+            if !maze.is_inside(x as i32, y as i32) {
+                continue;
+            }
+            grid[y * 2 + 1][x * 2 + 1] = match cell.terrain {
+                Terrain::Road => style.color_path,
+                Terrain::Grass => style.color_grass,
+                Terrain::Mud => style.color_mud,
+                Terrain::Water => style.color_water,
+            };
+        }
+    }
+
+    if let (true, Some(run)) = (with_path, &maze.last_run) {
+        for (i, &(x, y)) in run.path.iter().enumerate() {
+            grid[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = style.color_solution;
+            if let Some(&(nx, ny)) = run.path.get(i + 1) {
+                grid[(y + ny + 1) as usize][(x + nx + 1) as usize] = style.color_solution;
+            }
+        }
+    }
+
+    let (sx, sy) = maze.start;
+    grid[(sy * 2 + 1) as usize][(sx * 2 + 1) as usize] = style.color_start;
+    let (gx, gy) = maze.goal;
+    grid[(gy * 2 + 1) as usize][(gx * 2 + 1) as usize] = style.color_goal;
+    grid
+}
+
+/// RGB value of a terminal color, using the usual xterm palette for named colors
+pub fn to_rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb { r, g, b } => [r, g, b],
+        Color::Black | Color::Reset => [0, 0, 0],
+        Color::DarkGrey => [128, 128, 128],
+        Color::Red => [255, 0, 0],
+        Color::DarkRed => [128, 0, 0],
+        Color::Green => [0, 255, 0],
+        Color::DarkGreen => [0, 128, 0],
+        Color::Yellow => [255, 255, 0],
+        Color::DarkYellow => [128, 128, 0],
+        Color::Blue => [0, 0, 255],
+        Color::DarkBlue => [0, 0, 128],
+        Color::Magenta => [255, 0, 255],
+        Color::DarkMagenta => [128, 0, 128],
+        Color::Cyan => [0, 255, 255],
+        Color::DarkCyan => [0, 128, 128],
+        Color::White => [255, 255, 255],
+        Color::Grey => [192, 192, 192],
+        // 256 color palette, shown as a grey of the same index
+        Color::AnsiValue(value) => [value, value, value],
+    }
+}
+
+/// The maze as RGB pixels, returns the width, height and the pixels row by row.
+/// Fails without allocating anything when the image would exceed `MAX_PIXELS`.
+pub fn render_rgb(
+    maze: &Maze,
+    style: &ImageStyle,
+    with_path: bool,
+) -> io::Result<(usize, usize, Vec<u8>)> {
+    let width = style.offset(maze.width * 2 + 1);
+    let height = style.offset(maze.height * 2 + 1);
+    if width.saturating_mul(height) > MAX_PIXELS {
+        return Err(io::Error::other(format!(
+            "a {}x{} image is too large, use smaller cells or walls",
+            width, height
+        )));
+    }
+    let grid = color_grid(maze, style, with_path);
+    let mut pixels = vec![0; width * height * 3];
+    for (gy, row) in grid.iter().enumerate() {
+        for (gx, &color) in row.iter().enumerate() {
+            let rgb = to_rgb(color);
+            for py in style.offset(gy)..style.offset(gy) + style.span(gy) {
+                for px in style.offset(gx)..style.offset(gx) + style.span(gx) {
+                    let i = (py * width + px) * 3;
+                    pixels[i..i + 3].copy_from_slice(&rgb);
+                }
+            }
+        }
+    }
+    Ok((width, height, pixels))
+}
+
+/// Binary PPM (P6)
+pub fn save_ppm(maze: &Maze, style: &ImageStyle, with_path: bool, path: &Path) -> io::Result<()> {
+    let (width, height, pixels) = render_rgb(maze, style, with_path)?;
+    let mut file = BufWriter::new(File::create(path)?);
+    write!(file, "P6\n{} {}\n255\n", width, height)?;
+    file.write_all(&pixels)?;
+    file.flush()
+}
+
+pub fn save_png(maze: &Maze, style: &ImageStyle, with_path: bool, path: &Path) -> io::Result<()> {
+    let (width, height, pixels) = render_rgb(maze, style, with_path)?;
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&pixels).map_err(io::Error::other)
+}
+
+/// SVG with one rectangle per element of the wall grid, so it scales without blurring
+pub fn to_svg(maze: &Maze, style: &ImageStyle, with_path: bool) -> String {
+    let grid = color_grid(maze, style, with_path);
+    let width = style.offset(grid[0].len());
+    let height = style.offset(grid.len());
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        width, height, width, height
+    );
+    for (gy, row) in grid.iter().enumerate() {
+        for (gx, &color) in row.iter().enumerate() {
+            let [r, g, b] = to_rgb(color);
+            svg.push_str(&format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>\n",
+                style.offset(gx),
+                style.offset(gy),
+                style.span(gx),
+                style.span(gy),
+                r,
+                g,
+                b
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save_svg(maze: &Maze, style: &ImageStyle, with_path: bool, path: &Path) -> io::Result<()> {
+    std::fs::write(path, to_svg(maze, style, with_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze_and_style(cell_size: usize, wall_thickness: usize) -> (Maze, ImageStyle) {
+        let mut maze = Maze::new_open(4, 3);
+        maze.start = (0, 0);
+        maze.goal = (3, 2);
+        let style = ImageStyle::from_scene(
+            &MazeScene::new(maze.clone(), 0, 0, 1),
+            cell_size,
+            wall_thickness,
+        );
+        (maze, style)
+    }
+
+    // Hex fill of an SVG rect
+    fn fill(color: Color) -> String {
+        let [r, g, b] = to_rgb(color);
+        format!("fill=\"#{:02x}{:02x}{:02x}\"", r, g, b)
+    }
+
+    #[test]
+    fn raster_size_follows_the_grid() {
+        let (maze, style) = maze_and_style(5, 2);
+        let (width, height, pixels) = render_rgb(&maze, &style, false).unwrap();
+        assert_eq!(width, style.offset(2 * 4 + 1));
+        assert_eq!(height, style.offset(2 * 3 + 1));
+        assert_eq!((width, height), (4 * 7 + 2, 3 * 7 + 2));
+        assert_eq!(pixels.len(), width * height * 3);
+    }
+
+    #[test]
+    fn ppm_header() {
+        let (maze, style) = maze_and_style(5, 2);
+        let path = std::env::temp_dir().join(format!("maze_header_{}.ppm", std::process::id()));
+        save_ppm(&maze, &style, false, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let header = b"P6\n30 23\n255\n";
+        assert_eq!(&bytes[..header.len()], header);
+        assert_eq!(bytes.len(), header.len() + 30 * 23 * 3);
+    }
+
+    #[test]
+    fn oversized_image_is_refused() {
+        // Several terabytes of pixels if it were allocated
+        let (maze, style) = maze_and_style(1_000_000, 1_000_000);
+        assert!(render_rgb(&maze, &style, false).is_err());
+    }
+
+    #[test]
+    fn svg_has_one_rect_per_grid_position() {
+        let (maze, style) = maze_and_style(5, 2);
+        let svg = to_svg(&maze, &style, false);
+        assert_eq!(svg.matches("<rect").count(), (2 * 4 + 1) * (2 * 3 + 1));
+        let rect_at = |(x, y): (i32, i32)| {
+            let position = format!(
+                "x=\"{}\" y=\"{}\"",
+                style.offset(x as usize * 2 + 1),
+                style.offset(y as usize * 2 + 1)
+            );
+            svg.lines()
+                .find(|line| line.contains(&position))
+                .unwrap()
+                .to_string()
+        };
+        assert!(rect_at(maze.start).contains(&fill(style.color_start)));
+        assert!(rect_at(maze.goal).contains(&fill(style.color_goal)));
+        assert!(!rect_at((1, 0)).contains(&fill(style.color_start)));
+    }
+}
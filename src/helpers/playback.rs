@@ -0,0 +1,148 @@
+// Playback controls shared between a visualization and its worker thread
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use console_engine::{ConsoleEngine, KeyCode};
+
+pub const DEFAULT_DELAY_MS: u64 = 50;
+const MIN_DELAY_MS: u64 = 1;
+const MAX_DELAY_MS: u64 = 2000;
+// Granularity of the worker sleep, so pausing and quitting stay responsive
+const POLL_MS: u64 = 10;
+
+pub struct Playback {
+    running: AtomicBool,
+    paused: AtomicBool,
+    // Single steps requested while paused
+    pending_steps: AtomicU32,
+    // Run to completion without waiting between steps
+    instant: AtomicBool,
+    // False for animations that never finish, which would run without a pause forever
+    instant_allowed: bool,
+    delay_ms: AtomicU64,
+}
+
+impl Playback {
+    pub fn new() -> Playback {
+        Playback {
+            running: AtomicBool::new(true),
+            paused: AtomicBool::new(false),
+            pending_steps: AtomicU32::new(0),
+            instant: AtomicBool::new(false),
+            instant_allowed: true,
+            delay_ms: AtomicU64::new(DEFAULT_DELAY_MS),
+        }
+    }
+
+    /// Playback of an animation that never finishes, without instant finish
+    pub fn endless() -> Playback {
+        Playback {
+            instant_allowed: false,
+            ..Playback::new()
+        }
+    }
+
+    /// Handle the playback keys, returns true when the user asked to quit
+    pub fn handle_keys(&self, engine: &ConsoleEngine) -> bool {
+        if engine.is_key_pressed(KeyCode::Char('p')) {
+            self.paused.store(true, Ordering::SeqCst);
+        }
+        if engine.is_key_pressed(KeyCode::Char('r')) {
+            self.paused.store(false, Ordering::SeqCst);
+        }
+        if engine.is_key_pressed(KeyCode::Char('s')) {
+            self.paused.store(true, Ordering::SeqCst);
+            self.pending_steps.fetch_add(1, Ordering::SeqCst);
+        }
+        if engine.is_key_pressed(KeyCode::Char('+')) || engine.is_key_pressed(KeyCode::Char('=')) {
+            let delay = self.delay_ms.load(Ordering::SeqCst);
+            self.delay_ms
+                .store((delay / 2).max(MIN_DELAY_MS), Ordering::SeqCst);
+        }
+        if engine.is_key_pressed(KeyCode::Char('-')) {
+            let delay = self.delay_ms.load(Ordering::SeqCst);
+            self.delay_ms
+                .store((delay * 2).min(MAX_DELAY_MS), Ordering::SeqCst);
+        }
+        if engine.is_key_pressed(KeyCode::Char('f')) && self.instant_allowed {
+            self.instant.store(true, Ordering::SeqCst);
+            self.paused.store(false, Ordering::SeqCst);
+        }
+        if engine.is_key_pressed(KeyCode::Char('q')) {
+            self.stop();
+            return true;
+        }
+        false
+    }
+
+    /// Called by the worker before each step. Blocks while paused or until the
+    /// step delay is over, returns false once the visualization is stopped.
+    pub fn wait_step(&self) -> bool {
+        let mut waited = 0;
+        loop {
+            if !self.running.load(Ordering::SeqCst) {
+                return false;
+            }
+            if self.instant.load(Ordering::SeqCst) {
+                return true;
+            }
+            let delay = self.delay_ms.load(Ordering::SeqCst);
+            let paused = self.paused.load(Ordering::SeqCst);
+            if paused {
+                let pending = self.pending_steps.load(Ordering::SeqCst);
+                if pending > 0 {
+                    self.pending_steps.fetch_sub(1, Ordering::SeqCst);
+                    return true;
+                }
+            } else if waited >= delay {
+                return true;
+            }
+
+            let nap = if paused {
+                POLL_MS
+            } else {
+                (delay - waited).min(POLL_MS)
+            };
+            thread::sleep(Duration::from_millis(nap));
+            waited += nap;
+        }
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Current speed, for the info panel
+    pub fn describe_speed(&self) -> String {
+        if self.instant.load(Ordering::SeqCst) {
+            return "Speed: instant".to_string();
+        }
+        let delay = self.delay_ms.load(Ordering::SeqCst);
+        let state = if self.is_paused() { " (paused)" } else { "" };
+        format!(
+            "Speed: {:.1} steps per second ({}ms per step){}",
+            1000.0 / delay as f64,
+            delay,
+            state
+        )
+    }
+}
+
+/// Print the playback state and key bindings, starting at line `y`
+pub fn print_playback_help(engine: &mut ConsoleEngine, playback: &Playback, y: i32) {
+    engine.print(0, y, &playback.describe_speed());
+    engine.print(0, y + 1, "Press 'q' to quit");
+    engine.print(0, y + 2, "Press 'p' to pause");
+    engine.print(0, y + 3, "Press 'r' to resume");
+    engine.print(0, y + 4, "Press 's' to step");
+    engine.print(0, y + 5, "Press '+'/'-' to change the speed");
+    if playback.instant_allowed {
+        engine.print(0, y + 6, "Press 'f' to finish instantly");
+    }
+}
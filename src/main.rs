@@ -6,18 +6,30 @@ mod algorithms {
 
 mod helpers {
     pub mod engine_helpers;
+    pub mod image_export;
+    pub mod playback;
 }
 
 mod data {
     pub mod data_structures;
+    pub mod maze_analysis;
+    pub mod maze_graph;
+    pub mod maze_io;
+    pub mod maze_mask;
+    pub mod maze_postprocess;
 }
 
 mod menu {
+    pub mod analysis_panel;
     pub mod button;
     pub mod dropdown;
+    pub mod file_menu;
     pub mod form;
+    pub mod input;
     pub mod main_menu;
+    pub mod maze_editor;
     pub mod maze_menu;
+    pub mod maze_runner;
     pub mod maze_scene;
     pub mod menu;
     pub mod menu_handler;
@@ -0,0 +1,159 @@
+// Analysis panel: the texture measures of a maze printed next to it, over a
+// heatmap of the distances from the start.
+
+use console_engine::ConsoleEngine;
+use console_engine::KeyCode;
+
+use crate::data::data_structures::Maze;
+use crate::data::maze_analysis::MazeAnalysis;
+use crate::menu::maze_scene::MazeScene;
+
+// Corridor lengths grouped for the histogram, as (first, last) lengths
+const CORRIDOR_BUCKETS: [(usize, usize); 6] =
+    [(1, 1), (2, 2), (3, 4), (5, 8), (9, 16), (17, usize::MAX)];
+const HISTOGRAM_WIDTH: usize = 16;
+
+// Path drawn over the maze, 'p' cycles through them
+#[derive(Clone, Copy, PartialEq)]
+enum ShownPath {
+    None,
+    Solution,
+    Longest,
+}
+
+impl ShownPath {
+    fn next(self) -> ShownPath {
+        match self {
+            ShownPath::None => ShownPath::Solution,
+            ShownPath::Solution => ShownPath::Longest,
+            ShownPath::Longest => ShownPath::None,
+        }
+    }
+}
+
+pub fn run_analysis_panel(engine: &mut ConsoleEngine, maze: &Maze) {
+    let analysis = maze.analyze();
+
+    let mut maze = maze.clone();
+    maze.reset_visited();
+    let screen_size = termsize::get().unwrap();
+    let mut scene = MazeScene::new(
+        maze.clone(),
+        (screen_size.cols as i32 / 2 - maze.width as i32).max(0),
+        (screen_size.rows as i32 / 2 - maze.height as i32).max(0),
+        2,
+    );
+    let mut show_heat = true;
+    let mut shown_path = ShownPath::Solution;
+    let mut changed = true;
+
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+
+        if engine.is_key_pressed(KeyCode::Char('q')) {
+            break;
+        }
+        if engine.is_key_pressed(KeyCode::Char('h')) {
+            show_heat = !show_heat;
+            changed = true;
+        }
+        if engine.is_key_pressed(KeyCode::Char('p')) {
+            shown_path = shown_path.next();
+            changed = true;
+        }
+        if changed {
+            scene.highlights.heat = if show_heat {
+                analysis.distances.clone()
+            } else {
+                Vec::new()
+            };
+            scene.highlights.path = match shown_path {
+                ShownPath::None => Vec::new(),
+                ShownPath::Solution => analysis.solution.clone().unwrap_or_default(),
+                ShownPath::Longest => analysis.longest_path.clone(),
+            };
+            changed = false;
+        }
+
+        scene.draw(engine, false, false);
+        print_analysis(engine, &maze, &analysis);
+        engine.print(0, 22, "Press 'h' to show or hide the heatmap");
+        engine.print(0, 23, "Press 'p' to switch the path shown");
+        engine.print(0, 24, "Press 'q' to quit");
+
+        engine.draw();
+    }
+}
+
+fn print_analysis(engine: &mut ConsoleEngine, maze: &Maze, analysis: &MazeAnalysis) {
+    let cells = maze.inside_count();
+    let title = match &maze.origin {
+        Some(origin) => format!("{}, seed {}", origin.generator, origin.seed),
+        None => "Edited maze".to_string(),
+    };
+    engine.print(0, 0, "Maze Analysis");
+    engine.print(0, 1, &title);
+    engine.print(
+        0,
+        3,
+        &format!(
+            "Dead ends: {} ({:.1}%)",
+            analysis.dead_ends,
+            100.0 * analysis.dead_ends as f64 / cells as f64
+        ),
+    );
+    engine.print(0, 4, &format!("Junctions: {}", analysis.junctions));
+    engine.print(
+        0,
+        5,
+        &format!("Branching factor: {:.2}", analysis.branching_factor),
+    );
+    engine.print(0, 6, &format!("River factor: {:.2}", analysis.river_factor));
+    engine.print(
+        0,
+        7,
+        &format!("Longest path: {}", analysis.longest_path.len()),
+    );
+    let solution = match &analysis.solution {
+        Some(path) => path.len().to_string(),
+        None => "unreachable".to_string(),
+    };
+    engine.print(0, 8, &format!("Solution length: {}", solution));
+
+    engine.print(
+        0,
+        10,
+        &format!(
+            "Corridors: {}, mean length {:.1}",
+            analysis.corridor_count(),
+            analysis.mean_corridor_length()
+        ),
+    );
+    let counts: Vec<usize> = CORRIDOR_BUCKETS
+        .iter()
+        .map(|&(first, last)| {
+            analysis
+                .corridor_lengths
+                .iter()
+                .enumerate()
+                .filter(|&(length, _)| length >= first && length <= last)
+                .map(|(_, count)| count)
+                .sum()
+        })
+        .collect();
+    let most = counts.iter().copied().max().unwrap_or(0).max(1);
+    for (i, (&(first, last), count)) in CORRIDOR_BUCKETS.iter().zip(&counts).enumerate() {
+        let label = match (first, last) {
+            (first, last) if first == last => first.to_string(),
+            (first, usize::MAX) => format!("{}+", first),
+            (first, last) => format!("{}-{}", first, last),
+        };
+        let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(most));
+        engine.print(
+            0,
+            11 + i as i32,
+            &format!("{:>6} {:>4} {}", label, count, bar),
+        );
+    }
+}
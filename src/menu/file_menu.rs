@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use crate::data::data_structures::Maze;
+use crate::helpers::image_export::{save_png, save_ppm, save_svg, ImageStyle};
+
+use crate::menu::button::Button;
+use crate::menu::dropdown::DropDown;
+use crate::menu::input::{NumberInput, TextInput};
+use crate::menu::maze_scene::MazeScene;
+use console_engine::ConsoleEngine;
+use console_engine::KeyCode;
+
+use crate::menu::menu_handler::MenuHandler;
+use crate::menu::theme::default_theme;
+
+const ACTIONS: [&str; 7] = [
+    "Save ASCII",
+    "Load ASCII",
+    "Save JSON",
+    "Load JSON",
+    "Export PNG",
+    "Export PPM",
+    "Export SVG",
+];
+
+// Run the file menu, to save the current maze, load one or export it as an image.
+// A loaded maze becomes the current maze, ready to be solved from the pathfinding menu.
+pub fn run_file_menu(engine: &mut ConsoleEngine, current_maze: &mut Option<Maze>) {
+    let file_menu = DropDown {
+        x: 5,
+        y: 5,
+        width: 24,
+        height: 1,
+        items: ACTIONS.iter().map(|action| action.to_string()).collect(),
+        selected: 0,
+        opened: false,
+        confirmed: false,
+        color: default_theme().color,
+        color_selected: default_theme().color_selected,
+        bg_color: default_theme().bg_color,
+        button: Button::new(5, 5, 24, 1, "Save / Load"),
+    };
+    let mut menu_handler = MenuHandler::new(Box::new(file_menu));
+
+    let mut path_input = TextInput::new(40, 5, "File", "maze.txt");
+    // Image sizes in pixels
+    let mut size_inputs = [
+        NumberInput::new(40, 7, "Cell size", "16"),
+        NumberInput::new(40, 9, "Wall thickness", "4"),
+    ];
+    let mut focused: Option<usize> = None;
+    let mut with_path = true;
+    // Result of the last action
+    let mut message = String::new();
+
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+        menu_handler.draw(engine);
+
+        // Tab cycles through the fields, then back to the actions.
+        // Keys go to the focused field, so 'q' can be typed in the file name
+        if engine.is_key_pressed(KeyCode::Tab) {
+            focused = match focused {
+                None => Some(0),
+                Some(i) if i < size_inputs.len() => Some(i + 1),
+                Some(_) => None,
+            };
+        } else if focused.is_none() {
+            menu_handler.handle_input(engine);
+            if engine.is_key_pressed(KeyCode::Char('p')) {
+                with_path = !with_path;
+            }
+        }
+        path_input.selected = focused == Some(0);
+        path_input.handle_key_event(engine);
+        path_input.draw(engine);
+        for (i, input) in size_inputs.iter_mut().enumerate() {
+            input.selected = focused == Some(i + 1);
+            input.handle_key_event(engine);
+            input.draw(engine);
+        }
+        let path_state = if with_path { "shown" } else { "hidden" };
+        engine.print(40, 11, &format!("Solution in images: {}", path_state));
+        engine.print(40, 13, "Tab to edit the file name and image sizes");
+        engine.print(40, 14, "Press 'p' to show or hide the solution in images");
+        engine.print(40, 16, &message);
+
+        if menu_handler.should_quit {
+            break;
+        }
+
+        if menu_handler.confirmed() {
+            let action = ACTIONS[menu_handler.get_selected()];
+            let path = Path::new(&path_input.text);
+            message = if action.starts_with("Load") {
+                load(action, path, current_maze)
+            } else if let Some(maze) = current_maze.as_ref() {
+                let style = ImageStyle::from_scene(
+                    &MazeScene::new(maze.clone(), 0, 0, 1),
+                    size_inputs[0].value().unwrap_or(16).min(256) as usize,
+                    size_inputs[1].value().unwrap_or(4).min(256) as usize,
+                );
+                save(action, path, maze, &style, with_path)
+            } else {
+                "No maze to save, generate or edit one first".to_string()
+            };
+            menu_handler.set_confirmed(false);
+        }
+
+        engine.draw();
+    }
+}
+
+// Load the maze at `path` as the current maze, returns the message to show
+fn load(action: &str, path: &Path, current_maze: &mut Option<Maze>) -> String {
+    let loaded = if action == "Load ASCII" {
+        Maze::load_ascii(path)
+    } else {
+        Maze::load_json(path)
+    };
+    match loaded {
+        Ok(maze) => {
+            let message = format!(
+                "Loaded a {}x{} maze from {}",
+                maze.width,
+                maze.height,
+                path.display()
+            );
+            *current_maze = Some(maze);
+            message
+        }
+        Err(error) => format!("Could not load: {}", error),
+    }
+}
+
+// Save or export `maze`, returns the message to show.
+// Images get the extension of their format.
+fn save(action: &str, path: &Path, maze: &Maze, style: &ImageStyle, with_path: bool) -> String {
+    let (saved, path) = match action {
+        "Save ASCII" => (
+            maze.save_ascii(path).map_err(|e| e.to_string()),
+            path.to_path_buf(),
+        ),
+        "Save JSON" => (
+            maze.save_json(path).map_err(|e| e.to_string()),
+            path.to_path_buf(),
+        ),
+        _ => {
+            let extension = action.trim_start_matches("Export ").to_lowercase();
+            let path = path.with_extension(&extension);
+            let saved = match extension.as_str() {
+                "png" => save_png(maze, style, with_path, &path),
+                "ppm" => save_ppm(maze, style, with_path, &path),
+                _ => save_svg(maze, style, with_path, &path),
+            };
+            (saved.map_err(|e| e.to_string()), path)
+        }
+    };
+    match saved {
+        Ok(()) => format!("Saved to {}", path.display()),
+        Err(error) => format!("Could not save: {}", error),
+    }
+}
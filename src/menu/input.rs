@@ -0,0 +1,127 @@
+// Input fields, using console_engine.
+// Edited with the keyboard and backspace while selected.
+
+use console_engine::Color;
+use console_engine::ConsoleEngine;
+use console_engine::KeyCode;
+
+/// Field accepting digits only
+pub struct NumberInput {
+    pub x: i32,
+    pub y: i32,
+    pub label: String,
+    pub text: String,
+    // Shown instead of the value when the field is empty
+    pub placeholder: String,
+    pub max_len: usize,
+    pub selected: bool,
+}
+
+impl NumberInput {
+    pub fn new(x: i32, y: i32, label: &str, text: &str) -> NumberInput {
+        NumberInput {
+            x,
+            y,
+            label: label.to_string(),
+            text: text.to_string(),
+            placeholder: String::new(),
+            max_len: 10,
+            selected: false,
+        }
+    }
+
+    pub fn draw(&self, engine: &mut ConsoleEngine) {
+        let value = if self.text.is_empty() {
+            &self.placeholder
+        } else {
+            &self.text
+        };
+        draw_field(engine, self.x, self.y, &self.label, value, self.selected);
+    }
+
+    pub fn handle_key_event(&mut self, engine: &ConsoleEngine) {
+        if !self.selected {
+            return;
+        }
+        for digit in '0'..='9' {
+            if engine.is_key_pressed(KeyCode::Char(digit)) && self.text.len() < self.max_len {
+                self.text.push(digit);
+            }
+        }
+        if engine.is_key_pressed(KeyCode::Backspace) {
+            self.text.pop();
+        }
+    }
+
+    /// Parsed value, None when the field is empty
+    pub fn value(&self) -> Option<u64> {
+        self.text.parse().ok()
+    }
+}
+
+/// Field accepting any printable ASCII character, such as a file path
+pub struct TextInput {
+    pub x: i32,
+    pub y: i32,
+    pub label: String,
+    pub text: String,
+    pub max_len: usize,
+    pub selected: bool,
+}
+
+impl TextInput {
+    pub fn new(x: i32, y: i32, label: &str, text: &str) -> TextInput {
+        TextInput {
+            x,
+            y,
+            label: label.to_string(),
+            text: text.to_string(),
+            max_len: 60,
+            selected: false,
+        }
+    }
+
+    pub fn draw(&self, engine: &mut ConsoleEngine) {
+        draw_field(
+            engine,
+            self.x,
+            self.y,
+            &self.label,
+            &self.text,
+            self.selected,
+        );
+    }
+
+    pub fn handle_key_event(&mut self, engine: &ConsoleEngine) {
+        if !self.selected {
+            return;
+        }
+        for ch in ' '..='~' {
+            if engine.is_key_pressed(KeyCode::Char(ch)) && self.text.len() < self.max_len {
+                self.text.push(ch);
+            }
+        }
+        if engine.is_key_pressed(KeyCode::Backspace) {
+            self.text.pop();
+        }
+    }
+}
+
+fn draw_field(
+    engine: &mut ConsoleEngine,
+    x: i32,
+    y: i32,
+    label: &str,
+    value: &str,
+    selected: bool,
+) {
+    let fg = if selected { Color::Blue } else { Color::White };
+    let cursor = if selected { "_" } else { "" };
+    engine.print_fbg(
+        x,
+        y,
+        &format!("{}: {}{}", label, value, cursor),
+        fg,
+        Color::Black,
+    );
+}
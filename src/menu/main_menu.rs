@@ -1,7 +1,8 @@
 use super::menu_handler::MenuHandler;
+use crate::data::data_structures::Maze;
 use crate::menu::{
-    maze_menu::run_maze_menu, menu::Menu, pathfinding_menu::run_pathfinding_menu,
-    sort_menu::run_sort_menu,
+    file_menu::run_file_menu, maze_editor::run_maze_editor, maze_menu::run_maze_menu, menu::Menu,
+    pathfinding_menu::run_pathfinding_menu, sort_menu::run_sort_menu,
 };
 use console_engine::{ConsoleEngine, KeyCode, KeyModifiers};
 use termsize;
@@ -18,6 +19,9 @@ pub fn main_menu() {
     // Define the main menu options
     let menu_items = vec![
         "Maze Generation".to_string(),
+        "Pathfinding".to_string(),
+        "Maze Editor".to_string(),
+        "Save / Load".to_string(),
         "Sorting".to_string(),
         "Quit".to_string(),
     ];
@@ -38,6 +42,9 @@ pub fn main_menu() {
     //}
     let mut menu_handler = MenuHandler::new(Box::new(menu));
 
+    // Last generated or edited maze, shared between the maze menus
+    let mut current_maze: Option<Maze> = None;
+
     // Main loop for handling the menu
     loop {
         engine.wait_frame(); // Wait for the next frame
@@ -52,9 +59,12 @@ pub fn main_menu() {
         }
         if engine.is_key_pressed(KeyCode::Enter) {
             match menu_handler.get_selected() {
-                0 => run_maze_menu(&mut engine),
-                1 => run_sort_menu(&mut engine),
-                2 => break,
+                0 => run_maze_menu(&mut engine, &mut current_maze),
+                1 => run_pathfinding_menu(&mut engine, &mut current_maze),
+                2 => run_maze_editor(&mut engine, &mut current_maze),
+                3 => run_file_menu(&mut engine, &mut current_maze),
+                4 => run_sort_menu(&mut engine),
+                5 => break,
                 _ => (),
             }
         }
@@ -0,0 +1,178 @@
+// Maze editor, to build specific layouts by hand.
+// A cursor moves over the cells of the current maze (or of a new open maze)
+// and edits its walls, start and goal. Rectangular regions can be cleared or
+// filled with a random maze. The edited maze becomes the current maze.
+
+use console_engine::ConsoleEngine;
+use console_engine::KeyCode;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::algorithms::maze_generation::{MAZE_HEIGHT, MAZE_WIDTH};
+use crate::data::data_structures::Maze;
+use crate::menu::maze_scene::{Highlights, MazeScene};
+
+pub fn run_maze_editor(engine: &mut ConsoleEngine, current_maze: &mut Option<Maze>) {
+    let mut maze = match current_maze {
+        Some(maze) => maze.clone(),
+        None => Maze::new_open(MAZE_WIDTH, MAZE_HEIGHT),
+    };
+    maze.reset_visited();
+
+    let screen_size = termsize::get().unwrap();
+    let mut scene = MazeScene::new(
+        maze.clone(),
+        (screen_size.cols as i32 / 2 - maze.width as i32).max(0),
+        (screen_size.rows as i32 / 2 - maze.height as i32).max(0),
+        2,
+    );
+
+    let mut cursor = maze.start;
+    // First corner of the selected region, the cursor being the other one
+    let mut anchor: Option<(i32, i32)> = None;
+    // Each randomized region gets its own seed, shown so that it can be reproduced
+    let mut region_seed: u64 = rand::thread_rng().gen_range(0..100_000);
+    let mut last_seed: Option<u64> = None;
+    let mut edited = false;
+
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+
+        if engine.is_key_pressed(KeyCode::Char('q')) {
+            break;
+        }
+
+        let (x, y) = cursor;
+        if engine.is_key_pressed(KeyCode::Up) && y > 0 {
+            cursor.1 -= 1;
+        }
+        if engine.is_key_pressed(KeyCode::Down) && y < maze.height as i32 - 1 {
+            cursor.1 += 1;
+        }
+        if engine.is_key_pressed(KeyCode::Left) && x > 0 {
+            cursor.0 -= 1;
+        }
+        if engine.is_key_pressed(KeyCode::Right) && x < maze.width as i32 - 1 {
+            cursor.0 += 1;
+        }
+
+        // Toggle the wall on one side of the cursor, the outer border stays closed
+        let sides = [
+            ('w', (x, y - 1)),
+            ('d', (x + 1, y)),
+            ('s', (x, y + 1)),
+            ('a', (x - 1, y)),
+        ];
+        for (key, (nx, ny)) in sides {
+            let inside = nx >= 0 && ny >= 0 && nx < maze.width as i32 && ny < maze.height as i32;
+            if engine.is_key_pressed(KeyCode::Char(key)) && inside {
+                maze.toggle_wall(x, y, nx, ny);
+                edited = true;
+            }
+        }
+
+        // The start and goal cannot be placed outside the shape of a masked maze,
+        // nor on the same cell since the ASCII format has one marker per cell
+        let placeable = maze.is_inside(cursor.0, cursor.1);
+        if engine.is_key_pressed(KeyCode::Char('b')) && placeable && cursor != maze.goal {
+            maze.start = cursor;
+            edited = true;
+        }
+        if engine.is_key_pressed(KeyCode::Char('g')) && placeable && cursor != maze.start {
+            maze.goal = cursor;
+            edited = true;
+        }
+
+        if engine.is_key_pressed(KeyCode::Char('m')) {
+            anchor = Some(cursor);
+        }
+        if engine.is_key_pressed(KeyCode::Esc) {
+            anchor = None;
+        }
+        if let Some(corner) = anchor {
+            if engine.is_key_pressed(KeyCode::Char('c')) {
+                maze.clear_region(corner, cursor);
+                anchor = None;
+                edited = true;
+            } else if engine.is_key_pressed(KeyCode::Char('r')) {
+                let mut rng = StdRng::seed_from_u64(region_seed);
+                maze.randomize_region(corner, cursor, &mut rng);
+                last_seed = Some(region_seed);
+                region_seed += 1;
+                anchor = None;
+                edited = true;
+            }
+        }
+
+        scene.maze = maze.clone();
+        scene.highlights = Highlights {
+            head: Some(cursor),
+            frontier: region(anchor, cursor),
+            ..Highlights::default()
+        };
+        scene.draw(engine, false, false);
+        print_editor_help(engine, cursor, anchor.is_some(), last_seed);
+        print_connectivity(engine, &maze);
+
+        engine.draw();
+    }
+
+    // Edits make the recorded generator and solver run obsolete
+    if edited {
+        maze.origin = None;
+        maze.last_run = None;
+    }
+    *current_maze = Some(maze);
+}
+
+// Cells of the selected region, empty when no region is being selected
+fn region(anchor: Option<(i32, i32)>, cursor: (i32, i32)) -> Vec<(i32, i32)> {
+    let Some((ax, ay)) = anchor else {
+        return Vec::new();
+    };
+    let mut cells = Vec::new();
+    for y in ay.min(cursor.1)..=ay.max(cursor.1) {
+        for x in ax.min(cursor.0)..=ax.max(cursor.0) {
+            cells.push((x, y));
+        }
+    }
+    cells
+}
+
+fn print_editor_help(
+    engine: &mut ConsoleEngine,
+    cursor: (i32, i32),
+    selecting: bool,
+    last_seed: Option<u64>,
+) {
+    engine.print(0, 0, "Maze Editor");
+    engine.print(0, 2, &format!("Cursor: ({}, {})", cursor.0, cursor.1));
+    engine.print(0, 4, "Arrows to move the cursor");
+    engine.print(0, 5, "w/a/s/d to toggle a wall");
+    engine.print(0, 6, "Press 'b' to place the start");
+    engine.print(0, 7, "Press 'g' to place the goal");
+    engine.print(0, 8, "Press 'm' to mark a region corner");
+    if selecting {
+        engine.print(0, 9, "Press 'c' to clear the region");
+        engine.print(0, 10, "Press 'r' to randomize the region");
+        engine.print(0, 11, "Press Esc to cancel the region");
+    }
+    if let Some(seed) = last_seed {
+        engine.print(0, 12, &format!("Last region seed: {}", seed));
+    }
+    engine.print(0, 13, "Press 'q' to keep the maze and quit");
+}
+
+// Whether the edited maze is still perfect, and why not
+fn print_connectivity(engine: &mut ConsoleEngine, maze: &Maze) {
+    let connectivity = maze.connectivity();
+    if connectivity.is_perfect() {
+        engine.print(0, 15, "Perfect maze");
+        return;
+    }
+    engine.print(0, 15, &format!("Components: {}", connectivity.components));
+    if connectivity.has_loops() {
+        engine.print(0, 16, &format!("Loops: {}", connectivity.loops));
+    }
+}
@@ -1,30 +1,162 @@
 use crate::algorithms::maze_generation::*;
+use crate::data::data_structures::Maze;
+use crate::data::maze_io::MazeFileError;
+use crate::data::maze_mask::Mask;
+use std::path::Path;
+
+use crate::menu::analysis_panel::run_analysis_panel;
 
 use crate::menu::button::Button;
 use crate::menu::dropdown::DropDown;
+use crate::menu::input::{NumberInput, TextInput};
 use console_engine::ConsoleEngine;
 use console_engine::KeyCode;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use crate::menu::menu_handler::MenuHandler;
 use crate::menu::theme::default_theme;
 
 use super::menu_trait::MenuTrait;
 
-pub fn run_maze_menu(engine: &mut ConsoleEngine) {
-    let maze_items = vec![
-        "Recursive Backtracker".to_string(),
-        "Prim's Algorithm".to_string(),
-        "Kruskal's Algorithm".to_string(),
-        "Eller's Algorithm".to_string(),
-        "Hunt and Kill".to_string(),
-        "Aldous-Broder".to_string(),
-        "Wilson's Algorithm".to_string(),
-    ];
+// Generators available in the menu, with whether they never finish.
+// Adding an algorithm only requires implementing `MazeGenerator` and listing it here.
+pub fn generators() -> Vec<(&'static str, GeneratorConstructor, bool)> {
+    vec![
+        (
+            "Recursive Backtracker",
+            create::<RecursiveBacktracker>,
+            false,
+        ),
+        ("Prim's Algorithm", create::<Prim>, false),
+        ("Kruskal's Algorithm", create::<Kruskal>, false),
+        ("Eller's Algorithm", create::<Eller>, false),
+        (
+            "Eller's (Endless)",
+            |maze, rng| Box::new(Eller::endless(maze, rng)),
+            true,
+        ),
+        ("Hunt and Kill", create::<HuntAndKill>, false),
+        ("Aldous-Broder", create::<AldousBroder>, false),
+        ("Wilson's Algorithm", create::<Wilson>, false),
+        ("Binary Tree", create::<BinaryTree>, false),
+        ("Sidewinder", create::<Sidewinder>, false),
+        (
+            "Growing Tree (Newest)",
+            |maze, rng| {
+                Box::new(GrowingTree::with_policy(
+                    maze,
+                    rng,
+                    GrowingTreePolicy::Newest,
+                ))
+            },
+            false,
+        ),
+        (
+            "Growing Tree (Random)",
+            |maze, rng| {
+                Box::new(GrowingTree::with_policy(
+                    maze,
+                    rng,
+                    GrowingTreePolicy::Random,
+                ))
+            },
+            false,
+        ),
+        (
+            "Growing Tree (Oldest)",
+            |maze, rng| {
+                Box::new(GrowingTree::with_policy(
+                    maze,
+                    rng,
+                    GrowingTreePolicy::Oldest,
+                ))
+            },
+            false,
+        ),
+        (
+            "Growing Tree (Mixed)",
+            |maze, rng| {
+                Box::new(GrowingTree::with_policy(
+                    maze,
+                    rng,
+                    GrowingTreePolicy::Mixed,
+                ))
+            },
+            false,
+        ),
+        ("Recursive Division", create::<RecursiveDivision>, false),
+    ]
+}
+
+// Generators that produce a finished maze, the endless ones are only offered
+// in the maze menu and not when a maze is needed
+pub fn finite_generators() -> Vec<(&'static str, GeneratorConstructor)> {
+    generators()
+        .into_iter()
+        .filter(|&(_, _, endless)| !endless)
+        .map(|(name, create, _)| (name, create))
+        .collect()
+}
+
+// Shape of the generated maze, 'm' cycles through them
+#[derive(Clone, Copy, PartialEq)]
+enum MaskShape {
+    Rectangle,
+    Circle,
+    Text,
+    File,
+}
+
+impl MaskShape {
+    fn next(self) -> MaskShape {
+        match self {
+            MaskShape::Rectangle => MaskShape::Circle,
+            MaskShape::Circle => MaskShape::Text,
+            MaskShape::Text => MaskShape::File,
+            MaskShape::File => MaskShape::Rectangle,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            MaskShape::Rectangle => "Rectangle",
+            MaskShape::Circle => "Circle",
+            MaskShape::Text => "Text",
+            MaskShape::File => "Mask file",
+        }
+    }
+
+    // Mask of the maze, `input` is the text to write or the path of the mask file.
+    // The circle fills the maze size, text letters are `height` cells tall
+    fn build(
+        self,
+        input: &str,
+        width: usize,
+        height: usize,
+    ) -> Result<Option<Mask>, MazeFileError> {
+        match self {
+            MaskShape::Rectangle => Ok(None),
+            MaskShape::Circle => Ok(Some(Mask::circle(width, height))),
+            MaskShape::Text => Mask::text(input, height).map(Some),
+            MaskShape::File => Mask::load(Path::new(input)).map(Some),
+        }
+    }
+}
+
+// Run the maze generation menu.
+// The last generated maze is kept in `current_maze` so it can be solved afterwards.
+pub fn run_maze_menu(engine: &mut ConsoleEngine, current_maze: &mut Option<Maze>) {
+    let generators = generators();
+    let maze_items = generators
+        .iter()
+        .map(|(name, _, _)| name.to_string())
+        .collect();
 
     let mut maze_menu = Box::new(DropDown {
         x: 5,
         y: 5,
-        width: 20,
+        width: 24,
         height: 1,
         items: maze_items,
         selected: 0,
@@ -33,31 +165,133 @@ pub fn run_maze_menu(engine: &mut ConsoleEngine) {
         color: default_theme().color,
         color_selected: default_theme().color_selected,
         bg_color: default_theme().bg_color,
-        button: Button::new(5, 5, 20, 1, "Select Algorithm"),
+        button: Button::new(5, 5, 24, 1, "Select Algorithm"),
     });
 
     let mut menu_handler = MenuHandler::new(maze_menu);
 
+    // Seed and size of the maze, an empty seed picks a random one.
+    // Braid and sparseness are the post-processing percentages
+    let mut seed_input = NumberInput::new(40, 5, "Seed", "");
+    seed_input.placeholder = "random".to_string();
+    let mut fields = [
+        seed_input,
+        NumberInput::new(40, 7, "Width", &MAZE_WIDTH.to_string()),
+        NumberInput::new(40, 9, "Height", &MAZE_HEIGHT.to_string()),
+        NumberInput::new(40, 11, "Braid %", "0"),
+        NumberInput::new(40, 13, "Sparseness %", "0"),
+    ];
+    // Text written by the maze or path of the mask file, after the other fields
+    let mut mask_input = TextInput::new(40, 15, "Mask", "MAZE");
+    let mask_field = fields.len();
+    let mut focused: Option<usize> = None;
+    let mut terrain = TerrainMode::Uniform;
+    let mut mask_shape = MaskShape::Rectangle;
+    // Why the last maze could not be built or kept
+    let mut message = String::new();
+
     loop {
         engine.wait_frame();
         engine.clear_screen();
         menu_handler.draw(engine);
-        menu_handler.handle_input(engine);
+
+        // Tab cycles through the fields, then back to the algorithm list.
+        // Keys go to the mask field while it is focused, so any letter can be typed
+        if engine.is_key_pressed(KeyCode::Tab) {
+            focused = match focused {
+                None => Some(0),
+                Some(i) if i < mask_field => Some(i + 1),
+                Some(_) => None,
+            };
+        } else if focused != Some(mask_field) {
+            menu_handler.handle_input(engine);
+            if engine.is_key_pressed(KeyCode::Char('t')) {
+                terrain = terrain.next();
+            }
+            if engine.is_key_pressed(KeyCode::Char('m')) {
+                mask_shape = mask_shape.next();
+            }
+            if engine.is_key_pressed(KeyCode::Char('a')) {
+                if let Some(maze) = current_maze.as_ref() {
+                    run_analysis_panel(engine, maze);
+                }
+            }
+            if engine.is_key_pressed(KeyCode::Char('p')) {
+                if let Some(maze) = current_maze.as_mut() {
+                    let seed = fields[0]
+                        .value()
+                        .unwrap_or_else(|| rand::thread_rng().gen_range(0..100_000));
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    // Same order as after generating, see `finish_maze`
+                    maze.sparsify(fields[4].value().unwrap_or(0).min(100) as u8, &mut rng);
+                    maze.braid(fields[3].value().unwrap_or(0).min(100) as u8, &mut rng);
+                    // The old solution and the recipe no longer match the walls
+                    maze.last_run = None;
+                    maze.origin = None;
+                    message = format!("Post-processed the last maze with seed {}", seed);
+                }
+            }
+        }
+        for (i, field) in fields.iter_mut().enumerate() {
+            field.selected = focused == Some(i);
+            field.handle_key_event(engine);
+            field.draw(engine);
+        }
+        mask_input.selected = focused == Some(mask_field);
+        mask_input.handle_key_event(engine);
+        mask_input.draw(engine);
+        engine.print(40, 17, &format!("Terrain: {}", terrain.name()));
+        engine.print(40, 18, &format!("Shape: {}", mask_shape.name()));
+        engine.print(
+            40,
+            20,
+            "Tab to edit the seed, size, post-processing and mask",
+        );
+        engine.print(40, 21, "Press 't' to change the terrain");
+        engine.print(40, 22, "Press 'm' to change the shape");
+        if current_maze.is_some() {
+            engine.print(40, 23, "Press 'a' to analyze the last maze");
+            engine.print(40, 24, "Press 'p' to braid and sparsify it");
+        }
+        engine.print(40, 25, &message);
 
         if menu_handler.should_quit {
             break;
         }
 
         if menu_handler.confirmed() {
-            match menu_handler.get_selected() {
-                0 => recursive_backtracker(),
-                1 => prim_algorithm(),
-                2 => kruskal_algorithm(engine),
-                3 => eller_algorithm(),
-                4 => hunt_and_kill(),
-                5 => aldous_broder(),
-                6 => wilson_algorithm(),
-                _ => (),
+            if let Some(&(name, create, _)) = generators.get(menu_handler.get_selected()) {
+                let width = fields[1].value().unwrap_or(MAZE_WIDTH as u64).clamp(2, 200) as usize;
+                let height = fields[2]
+                    .value()
+                    .unwrap_or(MAZE_HEIGHT as u64)
+                    .clamp(2, 200) as usize;
+                match mask_shape.build(&mask_input.text, width, height) {
+                    Ok(mask) => {
+                        let settings = MazeSettings {
+                            width,
+                            height,
+                            seed: fields[0]
+                                .value()
+                                .unwrap_or_else(|| rand::thread_rng().gen_range(0..100_000)),
+                            terrain,
+                            braid: fields[3].value().unwrap_or(0).min(100) as u8,
+                            sparseness: fields[4].value().unwrap_or(0).min(100) as u8,
+                            mask,
+                        };
+                        match run_generator(engine, name, create, settings) {
+                            Some(maze) => {
+                                message.clear();
+                                *current_maze = Some(maze);
+                            }
+                            None => {
+                                message =
+                                    "Stopped before the end, the maze was not kept".to_string();
+                            }
+                        }
+                    }
+                    Err(error) => message = format!("Could not build the mask: {}", error),
+                }
             }
             menu_handler.set_confirmed(false);
         }
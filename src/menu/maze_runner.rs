@@ -0,0 +1,201 @@
+// Shared runner for the maze visualizations (generation and solving).
+// The algorithm is stepped in a worker thread while the scene is rendered
+// here, with the playback keys handled until 'q' is pressed.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use termsize;
+
+use crate::algorithms::maze_generation::StepOutcome;
+use crate::data::data_structures::Maze;
+use crate::helpers::engine_helpers::print_framerate;
+use crate::helpers::playback::{print_playback_help, Playback};
+use crate::menu::maze_scene::{Highlights, MazeScene};
+
+// Layout of the race view
+const RACE_PANEL_WIDTH: i32 = 36;
+const RACE_HEADER_HEIGHT: i32 = 4;
+const RACE_GAP: i32 = 2;
+
+/// Run `step` in a worker thread and render the maze until 'q' is pressed.
+/// `step` advances the algorithm and returns its outcome with the highlights to draw.
+/// Returns the maze as it was when the visualization was closed.
+pub fn run_animation<F>(
+    engine: &mut console_engine::ConsoleEngine,
+    maze: Maze,
+    description: &str,
+    highlights: Highlights,
+    playback: Playback,
+    mut step: F,
+) -> Maze
+where
+    F: FnMut(&mut Maze) -> (StepOutcome, Highlights) + Send + 'static,
+{
+    let width = maze.width;
+    let height = maze.height;
+    let screen_size = termsize::get().unwrap();
+    let mut scene = MazeScene::new(
+        maze.clone(),
+        (screen_size.cols as i32 / 2 - width as i32).max(0),
+        (screen_size.rows as i32 / 2 - height as i32).max(0),
+        2,
+    );
+    scene.highlights = highlights;
+
+    let maze = Arc::new(Mutex::new(maze));
+    let scene = Arc::new(Mutex::new(scene));
+    let maze_clone = Arc::clone(&maze);
+    let scene_clone = Arc::clone(&scene);
+
+    let playback = Arc::new(playback);
+    let playback_clone = Arc::clone(&playback);
+
+    let thread = thread::spawn(move || {
+        while playback_clone.wait_step() {
+            let mut maze = maze_clone.lock().unwrap();
+            let mut scene = scene_clone.lock().unwrap();
+            let (outcome, highlights) = step(&mut maze);
+            scene.maze = maze.clone();
+            scene.highlights = highlights;
+            if outcome == StepOutcome::Done {
+                break;
+            }
+        }
+    });
+
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+
+        let status = {
+            let scene = scene.lock().unwrap();
+            scene.draw(engine, false, false);
+            scene.highlights.status.clone()
+        };
+
+        if playback.handle_keys(engine) {
+            break;
+        }
+
+        print_framerate(engine);
+        print_run_info(engine, &playback, description, &status);
+
+        engine.draw();
+    }
+
+    // Wait for the thread to finish
+    thread.join().unwrap();
+
+    let maze = maze.lock().unwrap();
+    maze.clone()
+}
+
+/// Advances one algorithm of a race, returning its outcome and the highlights to draw
+pub type RaceStep = Box<dyn FnMut(&mut Maze) -> (StepOutcome, Highlights) + Send>;
+
+/// One algorithm of a race, with its own copy of the maze
+pub struct Racer {
+    pub name: String,
+    pub maze: Maze,
+    pub highlights: Highlights,
+    pub step: RaceStep,
+}
+
+/// Run several algorithms side by side, stepping them in lockstep in a single
+/// worker thread until 'q' is pressed.
+/// The first line of each racer's status is printed above its scene.
+pub fn run_race(engine: &mut console_engine::ConsoleEngine, description: &str, racers: Vec<Racer>) {
+    // Cells are one column wide so that the scenes fit next to each other,
+    // the playback help stays on the left
+    let mut scenes = Vec::new();
+    let mut names = Vec::new();
+    let mut workers = Vec::new();
+    let mut x = RACE_PANEL_WIDTH;
+    for racer in racers {
+        let mut scene = MazeScene::new(racer.maze.clone(), x, RACE_HEADER_HEIGHT, 1);
+        scene.highlights = racer.highlights;
+        x += (racer.maze.width * 2 + 1) as i32 + RACE_GAP;
+        scenes.push(scene);
+        names.push(racer.name);
+        workers.push((racer.maze, racer.step, false));
+    }
+    // Lockstep steps taken by each racer before it finished
+    let steps = Arc::new(Mutex::new(vec![0usize; scenes.len()]));
+    let scenes = Arc::new(Mutex::new(scenes));
+    let steps_clone = Arc::clone(&steps);
+    let scenes_clone = Arc::clone(&scenes);
+
+    let playback = Arc::new(Playback::new());
+    let playback_clone = Arc::clone(&playback);
+
+    let thread = thread::spawn(move || {
+        while playback_clone.wait_step() {
+            let mut scenes = scenes_clone.lock().unwrap();
+            let mut steps = steps_clone.lock().unwrap();
+            for (i, (maze, step, done)) in workers.iter_mut().enumerate() {
+                if *done {
+                    continue;
+                }
+                let (outcome, highlights) = step(maze);
+                scenes[i].maze = maze.clone();
+                scenes[i].highlights = highlights;
+                steps[i] += 1;
+                *done = outcome == StepOutcome::Done;
+            }
+            if workers.iter().all(|(_, _, done)| *done) {
+                break;
+            }
+        }
+    });
+
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+
+        {
+            let scenes = scenes.lock().unwrap();
+            let steps = steps.lock().unwrap();
+            for (i, scene) in scenes.iter().enumerate() {
+                scene.draw(engine, false, false);
+                let frontier =
+                    scene.highlights.frontier.len() + scene.highlights.frontier_alt.len();
+                let status = scene.highlights.status.lines().next().unwrap_or("");
+                engine.print(scene.x, 0, &names[i]);
+                engine.print(
+                    scene.x,
+                    1,
+                    &format!("Steps: {}, frontier: {}", steps[i], frontier),
+                );
+                engine.print(scene.x, 2, status);
+            }
+        }
+
+        if playback.handle_keys(engine) {
+            break;
+        }
+
+        print_framerate(engine);
+        engine.print(0, 3, description);
+        print_playback_help(engine, &playback, 4);
+
+        engine.draw();
+    }
+
+    thread.join().unwrap();
+}
+
+fn print_run_info(
+    engine: &mut console_engine::ConsoleEngine,
+    playback: &Playback,
+    name: &str,
+    status: &str,
+) {
+    // Algorithm, playback state and status of the run
+    engine.print(0, 0, "Run Info:");
+    engine.print(0, 3, &format!("Algorithm: {}", name));
+    print_playback_help(engine, playback, 4);
+    for (i, line) in status.lines().enumerate() {
+        engine.print(0, 12 + i as i32, line);
+    }
+}
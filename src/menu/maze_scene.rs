@@ -1,24 +1,92 @@
-// Maze scene is a simple scene that displays the animation of the maze generation algorithm.
+// Maze scene is a simple scene that displays the animation of the maze generation
+// and pathfinding algorithms.
 
-use crate::data::data_structures::Cell;
-use crate::data::data_structures::Maze;
+use crate::data::data_structures::{Direction, Maze, Terrain};
+use crate::data::maze_analysis::Distances;
 pub const EMPTY_CHAR: char = ' ';
 pub const WALL_CHAR: char = '#';
 pub const PATH_CHAR: char = ' ';
 pub const VISITED_CHAR: char = '.';
 pub const GOAL_CHAR: char = 'G';
 pub const START_CHAR: char = 'S';
+pub const CURRENT_CHAR: char = '*';
+pub const REJECTED_CHAR: char = 'x';
+pub const HEAD_CHAR: char = '@';
+pub const STACK_CHAR: char = 'o';
+pub const FRONTIER_CHAR: char = '+';
+pub const FRONTIER_ALT_CHAR: char = '^';
+pub const ROW_CHAR: char = '-';
+pub const SOLUTION_CHAR: char = '~';
+pub const GRASS_CHAR: char = ',';
+pub const MUD_CHAR: char = '%';
+pub const WATER_CHAR: char = '=';
 
 pub const WALL_COLOR: Color = Color::White;
 pub const PATH_COLOR: Color = Color::Red;
 pub const VISITED_COLOR: Color = Color::Green;
-pub const START_COLOR: Color = Color::Red;
+pub const START_COLOR: Color = Color::DarkYellow;
 pub const GOAL_COLOR: Color = Color::Blue;
+pub const CURRENT_COLOR: Color = Color::DarkCyan;
+pub const REJECTED_COLOR: Color = Color::DarkRed;
+pub const HEAD_COLOR: Color = Color::Magenta;
+pub const STACK_COLOR: Color = Color::Cyan;
+pub const FRONTIER_COLOR: Color = Color::DarkGrey;
+pub const FRONTIER_ALT_COLOR: Color = Color::DarkMagenta;
+pub const ROW_COLOR: Color = Color::DarkBlue;
+pub const SOLUTION_COLOR: Color = Color::Yellow;
+pub const GRASS_COLOR: Color = Color::Rgb {
+    r: 70,
+    g: 110,
+    b: 40,
+};
+pub const MUD_COLOR: Color = Color::Rgb {
+    r: 110,
+    g: 70,
+    b: 30,
+};
+pub const WATER_COLOR: Color = Color::Rgb {
+    r: 30,
+    g: 60,
+    b: 150,
+};
 
 use console_engine::pixel;
 use console_engine::Color;
 use console_engine::KeyCode;
 
+/// Algorithm state drawn on top of the maze
+#[derive(Clone, Default)]
+pub struct Highlights {
+    // Wall currently examined by the algorithm, as (x1, y1, x2, y2)
+    pub current_wall: Option<(i32, i32, i32, i32)>,
+    // Walls examined and kept since the last one removed
+    pub rejected_walls: Vec<(i32, i32, i32, i32)>,
+    // Cell the algorithm is currently working on
+    pub head: Option<(i32, i32)>,
+    // Direction the head faces, drawn in front of the head
+    pub facing: Option<Direction>,
+    // Cells still on the backtracking stack, bottom first
+    pub stack: Vec<(i32, i32)>,
+    // Cells next to the carved area that may be carved next
+    pub frontier: Vec<(i32, i32)>,
+    // Second frontier, for searches growing from both ends
+    pub frontier_alt: Vec<(i32, i32)>,
+    // Path found by a solver, start first
+    pub path: Vec<(i32, i32)>,
+    // Numbers written in cells, such as search costs. Only the last two digits
+    // fit in a cell, a later label for the same cell replaces the earlier one
+    pub labels: Vec<((i32, i32), i32)>,
+    // Distance of each cell from a source, drawn as a gradient over the plain
+    // cells and passages. Empty for no heatmap
+    pub heat: Distances,
+    // Row currently being processed
+    pub row: Option<i32>,
+    // Scroll so that the bottom of the maze stays on screen, for mazes growing downward
+    pub follow_bottom: bool,
+    // Extra information about the algorithm state, shown in the info panel
+    pub status: String,
+}
+
 pub struct MazeScene {
     pub maze: Maze,
     pub x: i32,
@@ -27,6 +95,20 @@ pub struct MazeScene {
     pub color_wall: Color,
     pub color_path: Color,
     pub color_visited: Color,
+    pub color_current: Color,
+    pub color_rejected: Color,
+    pub color_head: Color,
+    pub color_stack: Color,
+    pub color_frontier: Color,
+    pub color_frontier_alt: Color,
+    pub color_row: Color,
+    pub color_start: Color,
+    pub color_goal: Color,
+    pub color_solution: Color,
+    pub color_grass: Color,
+    pub color_mud: Color,
+    pub color_water: Color,
+    pub highlights: Highlights,
 }
 
 impl MazeScene {
@@ -39,6 +121,20 @@ impl MazeScene {
             color_wall: WALL_COLOR,
             color_path: PATH_COLOR,
             color_visited: VISITED_COLOR,
+            color_current: CURRENT_COLOR,
+            color_rejected: REJECTED_COLOR,
+            color_head: HEAD_COLOR,
+            color_stack: STACK_COLOR,
+            color_frontier: FRONTIER_COLOR,
+            color_frontier_alt: FRONTIER_ALT_COLOR,
+            color_row: ROW_COLOR,
+            color_start: START_COLOR,
+            color_goal: GOAL_COLOR,
+            color_solution: SOLUTION_COLOR,
+            color_grass: GRASS_COLOR,
+            color_mud: MUD_COLOR,
+            color_water: WATER_COLOR,
+            highlights: Highlights::default(),
         }
     }
 
@@ -62,85 +158,229 @@ impl MazeScene {
         engine: &mut console_engine::ConsoleEngine,
         colored: bool,
         random_colored: bool,
-        shortest_path: Vec<Cell>,
-        BFS: bool,
     ) {
         // Adjust the maze dimensions to include walls (same concept as adding rows/cols in Python)
         let new_width = self.maze.width * 2 + 1;
         let new_height = self.maze.height * 2 + 1;
 
-        let mut laby_with_walls = vec![vec![WALL_CHAR; new_width]; new_height];
+        // Walls and passages, the cells get their marks below
+        let mut laby_with_walls = self.maze.wall_grid();
 
-        // Construct the maze with walls around it
         for y in 0..self.maze.height {
             for x in 0..self.maze.width {
+                if !self.maze.is_inside(x as i32, y as i32) {
+                    continue;
+                }
                 let cell = self.maze.get_cell(x as i32, y as i32);
 
                 // Draw the cell's position
                 let draw_x = x * 2 + 1;
                 let draw_y = y * 2 + 1;
 
-                // Draw walls if needed
-                if cell.has_wall_north() {
-                    laby_with_walls[draw_y - 1][draw_x] = WALL_CHAR;
-                }
-                if cell.has_wall_south() {
-                    laby_with_walls[draw_y + 1][draw_x] = WALL_CHAR;
-                }
-                if cell.has_wall_west() {
-                    laby_with_walls[draw_y][draw_x - 1] = WALL_CHAR;
-                }
-                if cell.has_wall_east() {
-                    laby_with_walls[draw_y][draw_x + 1] = WALL_CHAR;
-                }
-
-                // Mark visited cells or path
-                if cell.visited {
-                    laby_with_walls[draw_y][draw_x] = VISITED_CHAR;
+                // Mark visited cells, or shade the others by terrain cost
+                laby_with_walls[draw_y][draw_x] = if cell.visited {
+                    VISITED_CHAR
                 } else {
-                    laby_with_walls[draw_y][draw_x] = PATH_CHAR;
+                    match cell.terrain {
+                        Terrain::Road => PATH_CHAR,
+                        Terrain::Grass => GRASS_CHAR,
+                        Terrain::Mud => MUD_CHAR,
+                        Terrain::Water => WATER_CHAR,
+                    }
+                };
+            }
+        }
+
+        // The wall between two cells sits halfway between them in the doubled grid
+        for &(x1, y1, x2, y2) in &self.highlights.rejected_walls {
+            laby_with_walls[(y1 + y2 + 1) as usize][(x1 + x2 + 1) as usize] = REJECTED_CHAR;
+        }
+        if let Some((x1, y1, x2, y2)) = self.highlights.current_wall {
+            laby_with_walls[(y1 + y2 + 1) as usize][(x1 + x2 + 1) as usize] = CURRENT_CHAR;
+        }
+
+        if let Some(y) = self.highlights.row {
+            for x in 0..self.maze.width {
+                if self.maze.is_inside(x as i32, y) {
+                    laby_with_walls[(y * 2 + 1) as usize][x * 2 + 1] = ROW_CHAR;
                 }
             }
         }
 
+        for &(x, y) in &self.highlights.frontier {
+            laby_with_walls[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = FRONTIER_CHAR;
+        }
+
+        for &(x, y) in &self.highlights.frontier_alt {
+            laby_with_walls[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = FRONTIER_ALT_CHAR;
+        }
+
+        // Stack cells and the passages joining them
+        for (i, &(x, y)) in self.highlights.stack.iter().enumerate() {
+            laby_with_walls[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = STACK_CHAR;
+            if let Some(&(px, py)) = self.highlights.stack.get(i + 1) {
+                laby_with_walls[(y + py + 1) as usize][(x + px + 1) as usize] = STACK_CHAR;
+            }
+        }
+        // Solved path, drawn over the explored cells and the frontier
+        for (i, &(x, y)) in self.highlights.path.iter().enumerate() {
+            laby_with_walls[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = SOLUTION_CHAR;
+            if let Some(&(nx, ny)) = self.highlights.path.get(i + 1) {
+                laby_with_walls[(y + ny + 1) as usize][(x + nx + 1) as usize] = SOLUTION_CHAR;
+            }
+        }
+
+        let (sx, sy) = self.maze.start;
+        laby_with_walls[(sy * 2 + 1) as usize][(sx * 2 + 1) as usize] = START_CHAR;
+        let (gx, gy) = self.maze.goal;
+        laby_with_walls[(gy * 2 + 1) as usize][(gx * 2 + 1) as usize] = GOAL_CHAR;
+
+        if let Some((x, y)) = self.highlights.head {
+            laby_with_walls[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = HEAD_CHAR;
+            if let Some((dx, dy)) = self.highlights.facing.map(|d| d.offset()) {
+                laby_with_walls[(y * 2 + 1 + dy) as usize][(x * 2 + 1 + dx) as usize] = HEAD_CHAR;
+            }
+        }
+
+        // Skip the top rows that do not fit on screen when following the bottom
+        let first_row = if self.highlights.follow_bottom {
+            let available = (engine.get_height() as i32 - self.y).max(1) as usize;
+            new_height.saturating_sub(available)
+        } else {
+            0
+        };
+
+        let heat = self.heat_colors(new_width, new_height);
+        let outside = self.outside_positions(new_width, new_height);
+
+        let mut labels = vec![vec![None; new_width]; new_height];
+        for &((x, y), value) in &self.highlights.labels {
+            labels[(y * 2 + 1) as usize][(x * 2 + 1) as usize] = Some(value);
+        }
+
         // Finally, render the maze to the console
-        (0..new_height).for_each(|y| {
+        (first_row..new_height).for_each(|y| {
             for x in 0..new_width {
+                // Left blank around the shape of a masked maze
+                if outside[y][x] {
+                    continue;
+                }
                 let ch = laby_with_walls[y][x];
+                let plain = matches!(
+                    ch,
+                    PATH_CHAR | VISITED_CHAR | GRASS_CHAR | MUD_CHAR | WATER_CHAR
+                );
 
-                let pixel_char;
-                // Draw based on mode (colored or not)
-                if colored {
-                    // Use different colors based on content
-                    pixel_char = match ch {
-                        WALL_CHAR => pixel::pxl_fg(WALL_CHAR, self.color_wall),
-                        VISITED_CHAR => pixel::pxl_fg(VISITED_CHAR, self.color_visited),
-                        _ => pixel::pxl_fg(PATH_CHAR, self.color_path),
-                    };
+                // Draw based on mode (colored characters or colored blocks)
+                // Start and goal keep their letter in block mode so they stand out
+                let pixel_char = if let (Some(color), true) = (heat[y][x], plain) {
+                    pixel::pxl_bg(' ', color)
+                } else if colored {
+                    pixel::pxl_fg(ch, self.choose_color(ch))
+                } else if ch == START_CHAR || ch == GOAL_CHAR {
+                    pixel::pxl_fbg(ch, Color::White, self.choose_color(ch))
                 } else {
-                    pixel_char = match ch {
-                        WALL_CHAR => pixel::pxl_bg(' ', self.color_wall),
-                        VISITED_CHAR => pixel::pxl_bg(' ', self.color_visited),
-                        _ => pixel::pxl_bg(' ', self.color_path),
-                    };
-                }
+                    pixel::pxl_bg(' ', self.choose_color(ch))
+                };
+                // Narrow cells keep the last digit only
+                let label: Option<Vec<char>> = labels[y][x].map(|value| {
+                    let skip = 2 - self.cell_size.clamp(1, 2) as usize;
+                    format!("{:>2}", value % 100).chars().skip(skip).collect()
+                });
                 for i in 0..self.cell_size {
+                    let pixel_char = match &label {
+                        Some(digits) if !colored => pixel::pxl_fbg(
+                            digits.get(i as usize).copied().unwrap_or(' '),
+                            Color::Black,
+                            self.choose_color(ch),
+                        ),
+                        _ => pixel_char,
+                    };
                     //
                     //        if sizeX % 2 == 0 and x == sizeX - 1:
                     //            return
                     //        elif sizeY % 2 == 0 and y == sizeY - 1:
                     //            return
-                    engine.set_pxl(self.x + (x * 2) as i32 + i, self.y + y as i32, pixel_char);
+                    engine.set_pxl(
+                        self.x + x as i32 * self.cell_size + i,
+                        self.y + (y - first_row) as i32,
+                        pixel_char,
+                    );
                 }
             }
         });
     }
 
+    // Positions of the doubled grid touching no cell inside the mask
+    fn outside_positions(&self, width: usize, height: usize) -> Vec<Vec<bool>> {
+        if self.maze.mask.is_none() {
+            return vec![vec![false; width]; height];
+        }
+        let mut outside = vec![vec![true; width]; height];
+        for (x, y) in self.maze.inside_cells() {
+            let (x, y) = (x as usize * 2, y as usize * 2);
+            for row in &mut outside[y..y + 3] {
+                row[x..x + 3].fill(false);
+            }
+        }
+        outside
+    }
+
+    // Heatmap color of each position of the doubled grid, passages get the
+    // mean of the cells they join
+    fn heat_colors(&self, width: usize, height: usize) -> Vec<Vec<Option<Color>>> {
+        let mut colors = vec![vec![None; width]; height];
+        let heat = &self.highlights.heat;
+        let Some(max) = heat.iter().flatten().flatten().max() else {
+            return colors;
+        };
+        let level = |distance: usize| distance as f64 / (*max).max(1) as f64;
+        for (y, row) in heat.iter().enumerate() {
+            for (x, distance) in row.iter().enumerate() {
+                let Some(distance) = *distance else {
+                    continue;
+                };
+                colors[y * 2 + 1][x * 2 + 1] = Some(heat_color(level(distance)));
+                for (nx, ny) in self.maze.open_neighbors(x as i32, y as i32) {
+                    if let Some(other) = heat[ny as usize][nx as usize] {
+                        let mean = (level(distance) + level(other)) / 2.0;
+                        colors[(y as i32 + ny + 1) as usize][(x as i32 + nx + 1) as usize] =
+                            Some(heat_color(mean));
+                    }
+                }
+            }
+        }
+        colors
+    }
+
     fn choose_color(&self, ch: char) -> Color {
         match ch {
             WALL_CHAR => self.color_wall,
             VISITED_CHAR => self.color_visited,
+            CURRENT_CHAR => self.color_current,
+            REJECTED_CHAR => self.color_rejected,
+            HEAD_CHAR => self.color_head,
+            STACK_CHAR => self.color_stack,
+            FRONTIER_CHAR => self.color_frontier,
+            FRONTIER_ALT_CHAR => self.color_frontier_alt,
+            ROW_CHAR => self.color_row,
+            START_CHAR => self.color_start,
+            GOAL_CHAR => self.color_goal,
+            SOLUTION_CHAR => self.color_solution,
+            GRASS_CHAR => self.color_grass,
+            MUD_CHAR => self.color_mud,
+            WATER_CHAR => self.color_water,
             _ => self.color_path,
         }
     }
 }
+
+// Blue for the nearest cells through to red for the furthest, `level` in [0, 1]
+fn heat_color(level: f64) -> Color {
+    let level = level.clamp(0.0, 1.0);
+    Color::Rgb {
+        r: (40.0 + 215.0 * level) as u8,
+        g: (60.0 + 120.0 * (1.0 - (2.0 * level - 1.0).abs())) as u8,
+        b: (255.0 * (1.0 - level)) as u8,
+    }
+}
@@ -1,24 +1,68 @@
+use crate::algorithms::maze_generation::{
+    generate_maze, MazeSettings, TerrainMode, MAZE_HEIGHT, MAZE_WIDTH,
+};
 use crate::algorithms::pathfinding::*;
+use crate::data::data_structures::Maze;
+use crate::menu::maze_menu::finite_generators;
 use crate::menu::theme::default_theme;
 
 use crate::menu::button::Button;
 use crate::menu::dropdown::DropDown;
-use console_engine::{ConsoleEngine, KeyCode};
+use console_engine::ConsoleEngine;
+use console_engine::KeyCode;
+use rand::Rng;
 
-use super::{menu_handler, menu_trait::MenuTrait};
+use super::menu_handler;
+
+// Solvers available in the menu.
+// Adding an algorithm only requires implementing `Solver` and listing it here.
+pub fn solvers() -> Vec<(&'static str, SolverConstructor)> {
+    vec![
+        ("BFS", create::<Bfs>),
+        ("DFS", create::<Dfs>),
+        ("Bidirectional BFS", create::<BidirectionalBfs>),
+        ("Dijkstra", |maze| Box::new(AStar::dijkstra(maze))),
+        ("A* (Manhattan)", |maze| {
+            Box::new(AStar::with_heuristic(maze, Heuristic::Manhattan))
+        }),
+        ("A* (Euclidean)", |maze| {
+            Box::new(AStar::with_heuristic(maze, Heuristic::Euclidean))
+        }),
+        ("A* (Chebyshev)", |maze| {
+            Box::new(AStar::with_heuristic(maze, Heuristic::Chebyshev))
+        }),
+        ("A* (Zero)", |maze| {
+            Box::new(AStar::with_heuristic(maze, Heuristic::Zero))
+        }),
+        ("Wall Follower (Left)", |maze| {
+            Box::new(WallFollower::with_hand(maze, Hand::Left))
+        }),
+        ("Wall Follower (Right)", |maze| {
+            Box::new(WallFollower::with_hand(maze, Hand::Right))
+        }),
+        ("Tremaux", create::<Tremaux>),
+        ("Dead-end Filling", create::<DeadEndFilling>),
+    ]
+}
 
 // Run the pathfinding menu.
-//  - This function displays the pathfinding algorithms menu.
-//  It allows the user to select a pathfinding algorithm to visualize.
-//  and the maze generation algorithm
+//  - This function first asks for the maze to solve: the current maze or a new
+//  one from any generator. It then displays the pathfinding algorithms menu,
+//  allowing the user to select a pathfinding algorithm to visualize.
 
-pub fn run_pathfinding_menu(engine: &mut ConsoleEngine) {
-    let pathfinding_items = vec!["BFS".to_string(), "DFS".to_string()];
+pub fn run_pathfinding_menu(engine: &mut ConsoleEngine, current_maze: &mut Option<Maze>) {
+    let Some(mut maze) = pick_maze(engine, current_maze) else {
+        return;
+    };
+    *current_maze = Some(maze.clone());
 
-    let mut pathfinding_menu = DropDown {
-        x: 0,
-        y: 0,
-        width: 20,
+    let solvers = solvers();
+    let pathfinding_items = solvers.iter().map(|(name, _)| name.to_string()).collect();
+
+    let pathfinding_menu = DropDown {
+        x: 5,
+        y: 5,
+        width: 24,
         height: 1,
         items: pathfinding_items,
         selected: 0,
@@ -27,10 +71,12 @@ pub fn run_pathfinding_menu(engine: &mut ConsoleEngine) {
         color: default_theme().color,
         color_selected: default_theme().color_selected,
         bg_color: default_theme().bg_color,
-        button: Button::new(5, 5, 20, 1, "Pathfinding"),
+        button: Button::new(5, 5, 24, 1, "Pathfinding"),
     };
 
     let mut menu_handler = menu_handler::MenuHandler::new(Box::new(pathfinding_menu));
+    // Solvers marked for a race, in the order they were marked
+    let mut racers: Vec<usize> = Vec::new();
     loop {
         engine.wait_frame();
         engine.clear_screen();
@@ -41,16 +87,96 @@ pub fn run_pathfinding_menu(engine: &mut ConsoleEngine) {
             break;
         }
 
-        if menu_handler.confirmed() {
-            match menu_handler.get_selected() {
-                0 => bfs(),
-                1 => dfs(),
-                _ => bfs(),
-            };
+        if engine.is_key_pressed(KeyCode::Char(' ')) {
+            let selected = menu_handler.get_selected();
+            if let Some(i) = racers.iter().position(|&r| r == selected) {
+                racers.remove(i);
+            } else {
+                racers.push(selected);
+            }
+        }
+        if engine.is_key_pressed(KeyCode::Char('r')) && racers.len() >= 2 {
+            let race: Vec<(&str, SolverConstructor)> = racers.iter().map(|&i| solvers[i]).collect();
+            run_solver_race(engine, &race, &maze);
+        }
 
+        let names: Vec<&str> = racers.iter().map(|&i| solvers[i].0).collect();
+        engine.print(40, 5, &format!("Race: {}", names.join(", ")));
+        engine.print(40, 7, "Space to add or remove the solver from the race");
+        engine.print(40, 8, "Press 'r' to race two or more solvers");
+
+        if menu_handler.confirmed() {
+            if let Some(&(name, create)) = solvers.get(menu_handler.get_selected()) {
+                if let Some(run) = run_solver(engine, name, create, &maze) {
+                    maze.last_run = Some(run);
+                    *current_maze = Some(maze.clone());
+                }
+            }
             menu_handler.set_confirmed(false);
         }
 
         engine.draw();
     }
 }
+
+// Ask which maze to solve, None if the user quit
+fn pick_maze(engine: &mut ConsoleEngine, current_maze: &Option<Maze>) -> Option<Maze> {
+    let generators = finite_generators();
+    let mut maze_items: Vec<String> = Vec::new();
+    if current_maze.is_some() {
+        maze_items.push("Current maze".to_string());
+    }
+    for (name, _) in &generators {
+        maze_items.push(format!("New: {}", name));
+    }
+
+    let maze_menu = DropDown {
+        x: 5,
+        y: 5,
+        width: 30,
+        height: 1,
+        items: maze_items,
+        selected: 0,
+        opened: false,
+        confirmed: false,
+        color: default_theme().color,
+        color_selected: default_theme().color_selected,
+        bg_color: default_theme().bg_color,
+        button: Button::new(5, 5, 30, 1, "Select Maze"),
+    };
+
+    let mut menu_handler = menu_handler::MenuHandler::new(Box::new(maze_menu));
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+        menu_handler.draw(engine);
+        menu_handler.handle_input(engine);
+
+        if menu_handler.should_quit {
+            return None;
+        }
+
+        if menu_handler.confirmed() {
+            let mut selected = menu_handler.get_selected();
+            if let Some(maze) = current_maze {
+                if selected == 0 {
+                    return Some(maze.clone());
+                }
+                selected -= 1;
+            }
+            let (name, create) = generators[selected];
+            let settings = MazeSettings {
+                width: MAZE_WIDTH,
+                height: MAZE_HEIGHT,
+                seed: rand::thread_rng().gen_range(0..100_000),
+                terrain: TerrainMode::Uniform,
+                braid: 0,
+                sparseness: 0,
+                mask: None,
+            };
+            return Some(generate_maze(name, create, settings));
+        }
+
+        engine.draw();
+    }
+}